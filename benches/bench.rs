@@ -8,88 +8,221 @@ mod tests {
 
     const FILENAME: &str = "maze_100_100_complex.png";
 
+    // Cells of the generated maze, it's 1001x1001 squares
+    const LARGE_SIZE: usize = 500;
+    const QUEUE_ITEMS: usize = 100_000;
+
     use super::maze_solver::get_image;
+    use super::maze_solver::img_to_maze;
     use super::maze_solver::img_to_vec;
     use super::maze_solver::maze_to_adjacency_list;
+    use super::maze_solver::{Graph, JunctionGraph};
 
     use super::maze_solver::algorithms::*;
+    use super::maze_solver::data_structures::Queue;
+    use super::maze_solver::generators::{generate, Algorithm};
 
     #[bench]
     fn bench_get_image(b: &mut Bencher) {
-        b.iter(|| {
-            get_image(String::from(FILENAME));
-        });
+        b.iter(|| get_image(String::from(FILENAME)).unwrap());
     }
 
     #[bench]
     fn bench_img_to_vec(b: &mut Bencher) {
-        let img = get_image(String::from(FILENAME));
+        let img = get_image(String::from(FILENAME)).unwrap();
 
-        b.iter(|| {
-            img_to_vec(&img);
-        });
+        b.iter(|| img_to_vec(&img));
     }
 
     #[bench]
     fn bench_simple_always_left(b: &mut Bencher) {
-        let img = get_image(String::from(FILENAME));
-        let maze = img_to_vec(&img);
+        let img = get_image(String::from(FILENAME)).unwrap();
+        let maze = img_to_maze(&img).unwrap();
 
-        b.iter(|| {
-            simple_always_left(&maze);
-        });
+        b.iter(|| simple_always_left(&maze));
     }
 
     #[bench]
     fn bench_wall_follower(b: &mut Bencher) {
-        let img = get_image(String::from(FILENAME));
-        let maze = img_to_vec(&img);
+        let img = get_image(String::from(FILENAME)).unwrap();
+        let maze = img_to_maze(&img).unwrap();
 
-        b.iter(|| {
-            wall_follower(&maze);
-        });
+        b.iter(|| wall_follower(&maze));
     }
 
     #[bench]
     fn bench_maze_to_adjacency_list(b: &mut Bencher) {
-        let img = get_image(String::from(FILENAME));
-        let maze = img_to_vec(&img);
+        let img = get_image(String::from(FILENAME)).unwrap();
+        let maze = img_to_maze(&img).unwrap();
 
-        b.iter(|| {
-            maze_to_adjacency_list(&maze);
-        });
+        b.iter(|| maze_to_adjacency_list(&maze));
+    }
+
+    #[bench]
+    fn bench_graph_from_maze_large(b: &mut Bencher) {
+        let maze = generate(Algorithm::RecursiveBacktracker, LARGE_SIZE, LARGE_SIZE, 0).unwrap();
+
+        b.iter(|| Graph::from_maze(&maze));
+    }
+
+    #[bench]
+    fn bench_compressed_graph_large(b: &mut Bencher) {
+        let maze = generate(Algorithm::RecursiveBacktracker, LARGE_SIZE, LARGE_SIZE, 0).unwrap();
+
+        b.iter(|| Graph::compressed(&maze));
+    }
+
+    #[bench]
+    fn bench_maze_to_adjacency_list_large(b: &mut Bencher) {
+        let maze = generate(Algorithm::RecursiveBacktracker, LARGE_SIZE, LARGE_SIZE, 0).unwrap();
+
+        b.iter(|| maze_to_adjacency_list(&maze));
     }
 
     #[bench]
     fn bench_depth_first_search(b: &mut Bencher) {
-        let img = get_image(String::from(FILENAME));
-        let maze = img_to_vec(&img);
-        let adj_list = maze_to_adjacency_list(&maze);
+        let img = get_image(String::from(FILENAME)).unwrap();
+        let maze = img_to_maze(&img).unwrap();
+        let graph = Graph::from_maze(&maze);
 
-        b.iter(|| {
-            depth_first(&maze, &adj_list);
-        });
+        b.iter(|| depth_first(&maze, &graph));
     }
 
     #[bench]
     fn bench_breadth_first_search(b: &mut Bencher) {
-        let img = get_image(String::from(FILENAME));
-        let maze = img_to_vec(&img);
-        let adj_list = maze_to_adjacency_list(&maze);
+        let img = get_image(String::from(FILENAME)).unwrap();
+        let maze = img_to_maze(&img).unwrap();
+        let graph = Graph::from_maze(&maze);
 
-        b.iter(|| {
-            breadth_first(&maze, &adj_list);
-        });
+        b.iter(|| breadth_first(&maze, &graph));
     }
 
     #[bench]
     fn bench_backtracking_breadth_first_search(b: &mut Bencher) {
-        let img = get_image(String::from(FILENAME));
-        let maze = img_to_vec(&img);
-        let adj_list = maze_to_adjacency_list(&maze);
+        let img = get_image(String::from(FILENAME)).unwrap();
+        let maze = img_to_maze(&img).unwrap();
+        let graph = Graph::from_maze(&maze);
 
+        b.iter(|| backtracking_breadth_first(&maze, &graph));
+    }
+
+    #[bench]
+    fn bench_a_star_manhattan(b: &mut Bencher) {
+        let img = get_image(String::from(FILENAME)).unwrap();
+        let maze = img_to_maze(&img).unwrap();
+
+        b.iter(|| a_star(&maze, Heuristic::Manhattan));
+    }
+
+    #[bench]
+    fn bench_a_star_euclidean(b: &mut Bencher) {
+        let img = get_image(String::from(FILENAME)).unwrap();
+        let maze = img_to_maze(&img).unwrap();
+
+        b.iter(|| a_star(&maze, Heuristic::Euclidean));
+    }
+
+    #[bench]
+    fn bench_a_star_chebyshev(b: &mut Bencher) {
+        let img = get_image(String::from(FILENAME)).unwrap();
+        let maze = img_to_maze(&img).unwrap();
+
+        b.iter(|| a_star(&maze, Heuristic::Chebyshev));
+    }
+
+    #[bench]
+    fn bench_a_star_zero(b: &mut Bencher) {
+        let img = get_image(String::from(FILENAME)).unwrap();
+        let maze = img_to_maze(&img).unwrap();
+
+        b.iter(|| a_star(&maze, Heuristic::Zero));
+    }
+
+    #[bench]
+    fn bench_queue(b: &mut Bencher) {
         b.iter(|| {
-            backtracking_breadth_first(&maze, &adj_list);
+            let mut queue = Queue::new();
+            let mut sum = 0;
+
+            for i in 0..QUEUE_ITEMS {
+                queue.enqueue(i);
+            }
+            while let Some(i) = queue.dequeue() {
+                sum += i;
+            }
+
+            sum
         });
     }
+
+    // What the queue used to do, to compare with the one above
+    #[bench]
+    fn bench_vec_remove_queue(b: &mut Bencher) {
+        b.iter(|| {
+            let mut queue = Vec::new();
+            let mut sum = 0;
+
+            for i in 0..QUEUE_ITEMS {
+                queue.push(i);
+            }
+            while !queue.is_empty() {
+                sum += queue.remove(0);
+            }
+
+            sum
+        });
+    }
+
+    #[bench]
+    fn bench_breadth_first_search_large(b: &mut Bencher) {
+        let maze = generate(Algorithm::RecursiveBacktracker, LARGE_SIZE, LARGE_SIZE, 0).unwrap();
+        let graph = Graph::from_maze(&maze);
+
+        b.iter(|| breadth_first(&maze, &graph));
+    }
+
+    #[bench]
+    fn bench_breadth_first_search_compressed_large(b: &mut Bencher) {
+        let maze = generate(Algorithm::RecursiveBacktracker, LARGE_SIZE, LARGE_SIZE, 0).unwrap();
+        let graph = Graph::compressed(&maze);
+
+        b.iter(|| breadth_first(&maze, &graph));
+    }
+
+    #[bench]
+    fn bench_junction_graph_large(b: &mut Bencher) {
+        let maze = generate(Algorithm::RecursiveBacktracker, LARGE_SIZE, LARGE_SIZE, 0).unwrap();
+
+        b.iter(|| JunctionGraph::new(&maze));
+    }
+
+    #[bench]
+    fn bench_dijkstra_large(b: &mut Bencher) {
+        let maze = generate(Algorithm::RecursiveBacktracker, LARGE_SIZE, LARGE_SIZE, 0).unwrap();
+
+        b.iter(|| dijkstra(&maze));
+    }
+
+    #[bench]
+    fn bench_junction_dijkstra_large(b: &mut Bencher) {
+        let maze = generate(Algorithm::RecursiveBacktracker, LARGE_SIZE, LARGE_SIZE, 0).unwrap();
+        let junctions = JunctionGraph::new(&maze);
+
+        b.iter(|| junctions.solve(&maze, graph_dijkstra));
+    }
+
+    #[bench]
+    fn bench_backtracking_breadth_first_search_large(b: &mut Bencher) {
+        let maze = generate(Algorithm::RecursiveBacktracker, LARGE_SIZE, LARGE_SIZE, 0).unwrap();
+        let graph = Graph::from_maze(&maze);
+
+        b.iter(|| backtracking_breadth_first(&maze, &graph));
+    }
+
+    #[bench]
+    fn bench_bidirectional_breadth_first_search_large(b: &mut Bencher) {
+        let maze = generate(Algorithm::RecursiveBacktracker, LARGE_SIZE, LARGE_SIZE, 0).unwrap();
+
+        b.iter(|| bidirectional_breadth_first(&maze));
+    }
 }
@@ -1,92 +1,89 @@
-use crate::constants::{PATH, WALL};
 use crate::data_structures::{Queue, Stack};
+use crate::graph::Graph;
+use crate::maze::{Direction, Maze};
+use crate::MazeError;
+use crate::{Pos, SolveResult, SolveStats};
 
-use std::collections::HashMap;
-use std::hash::BuildHasher;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::Instant;
 
 // ---------
 // Utilities
 // ---------
-fn get_start(maze: &[Vec<u8>]) -> (usize, usize) {
-    // Check the top
-    for (col, &square) in maze[0].iter().enumerate() {
-        if square != WALL {
-            return (0, col);
-        }
-    }
-
-    // Check the left
-    for (line, rows) in maze.iter().enumerate() {
-        if rows[0] != WALL {
-            return (line, 0);
-        }
-    }
-
-    eprintln!("Start point not found! Exiting..");
-    std::process::exit(1);
+pub fn get_start(maze: &Maze) -> Result<Pos, MazeError> {
+    maze.start()
 }
 
-fn get_end(maze: &[Vec<u8>]) -> (usize, usize) {
-    // Check the bottom
-    for (i, &row) in maze[maze.len() - 1].iter().enumerate() {
-        if row == PATH {
-            return (maze.len() - 1, i);
-        }
-    }
-
-    // Check the right
-    for (line, rows) in maze.iter().enumerate() {
-        if rows[maze[line].len() - 1] == PATH {
-            return (line, maze[line].len() - 1);
-        }
-    }
-
-    eprintln!("Ending point not found! Exiting..");
-    std::process::exit(1);
+pub fn get_end(maze: &Maze) -> Result<Pos, MazeError> {
+    maze.end()
 }
 
-fn path_up(maze: &[Vec<u8>], x: usize, y: usize, visited_square: &[Vec<bool>]) -> bool {
-    if x != 0 && maze[x - 1][y] == PATH && !visited_square[x - 1][y] {
-        return true;
-    }
-
-    false
+// The order the "always left" algorithms try the directions in
+const LEFT_FIRST: [Direction; 4] = [
+    Direction::Left,
+    Direction::Down,
+    Direction::Right,
+    Direction::Up,
+];
+
+// The open square in that direction, if it wasn't visited yet
+fn unvisited_step(maze: &Maze, visited: &[bool], pos: Pos, direction: Direction) -> Option<Pos> {
+    maze.open_step(pos, direction)
+        .filter(|&next| !visited[maze.index(next)])
 }
 
-fn path_right(maze: &[Vec<u8>], x: usize, y: usize, visited_square: &[Vec<bool>]) -> bool {
-    if y != (maze[x].len() - 1) && maze[x][y + 1] == PATH && !visited_square[x][y + 1] {
-        return true;
+// Follows the parents from the end back to the start,
+//  the path is returned from the start to the end
+fn backtrack(
+    maze: &Maze,
+    parents: &[Option<Pos>],
+    start: Pos,
+    end: Pos,
+) -> Result<Vec<Pos>, MazeError> {
+    let mut path = vec![end];
+    let mut current = end;
+
+    while current != start {
+        current = match parents[maze.index(current)] {
+            Some(parent) => parent,
+            None => return Err(MazeError::Unreachable),
+        };
+        path.push(current);
     }
 
-    false
-}
-
-fn path_left(maze: &[Vec<u8>], x: usize, y: usize, visited_square: &[Vec<bool>]) -> bool {
-    if y != 0 && maze[x][y - 1] == PATH && !visited_square[x][y - 1] {
-        return true;
-    }
+    path.reverse();
 
-    false
+    Ok(path)
 }
 
-fn path_down(maze: &[Vec<u8>], x: usize, y: usize, visited_square: &[Vec<bool>]) -> bool {
-    if x != (maze.len() - 1) && maze[x + 1][y] == PATH && !visited_square[x + 1][y] {
-        return true;
+fn solve_result(
+    maze: &Maze,
+    path: Option<Vec<Pos>>,
+    stats: SolveStats,
+    explored: Vec<Pos>,
+) -> SolveResult {
+    SolveResult {
+        cost: path.as_ref().map(|path| maze.path_cost(path)),
+        path,
+        explored,
+        stats,
     }
-
-    false
 }
 // ------------
 // </utilities>
 // ------------
 
 // ALWAYS LEFT
-pub fn simple_always_left(maze: &[Vec<u8>]) -> Vec<(usize, usize)> {
-    let mut solved_path = Vec::with_capacity(maze.len() * maze[0].len());
-    let mut visited_square = vec![vec![false; maze[0].len()]; maze.len()];
+// Never goes back, so the path is None when it gets stuck
+pub fn simple_always_left(maze: &Maze) -> Result<SolveResult, MazeError> {
+    let timer = Instant::now();
+
+    let start = get_start(maze)?;
+    let end = get_end(maze)?;
 
-    let start = get_start(&maze);
-    let end = get_end(&maze);
+    let mut solved_path = Vec::with_capacity(maze.len());
+    let mut visited_square = vec![false; maze.len()];
 
     solved_path.push(start);
 
@@ -94,317 +91,708 @@ pub fn simple_always_left(maze: &[Vec<u8>]) -> Vec<(usize, usize)> {
 
     // Solve it
     while current_path != end {
-        let (x, y) = current_path;
-
-        // Can I go left ?
-        if path_left(&maze, x, y, &visited_square) {
-            current_path = (x, y - 1);
-            visited_square[x][y] = true;
-
-            solved_path.push(current_path);
-            continue;
-        }
-
-        // Can I go down ?
-        if path_down(&maze, x, y, &visited_square) {
-            current_path = (x + 1, y);
-            visited_square[x][y] = true;
-
-            solved_path.push(current_path);
-            continue;
-        }
-
-        // Can I go right?
-        if path_right(&maze, x, y, &visited_square) {
-            current_path = (x, y + 1);
-            visited_square[x][y] = true;
+        // Can I go left? Down? Right? Up?
+        let next = LEFT_FIRST
+            .iter()
+            .find_map(|&direction| unvisited_step(maze, &visited_square, current_path, direction));
 
-            solved_path.push(current_path);
-            continue;
-        }
+        match next {
+            Some(next) => {
+                visited_square[maze.index(current_path)] = true;
+                current_path = next;
 
-        // Can I go up?
-        if path_up(&maze, x, y, &visited_square) {
-            current_path = (x - 1, y);
-            visited_square[x][y] = true;
-
-            solved_path.push(current_path);
-            continue;
+                solved_path.push(current_path);
+            }
+            // Stuck..
+            None => break,
         }
-
-        // Stuck..
-        break;
     }
 
-    solved_path
+    // The walk never backtracks, so if it got to the end it is the path
+    let path = if current_path == end {
+        Some(solved_path.clone())
+    } else {
+        None
+    };
+
+    Ok(solve_result(
+        maze,
+        path,
+        SolveStats {
+            nodes_expanded: solved_path.len(),
+            max_frontier: 0,
+            elapsed: timer.elapsed(),
+        },
+        solved_path,
+    ))
 }
 
 // -------------
 // Wall Follower
 // -------------
-#[derive(PartialEq)]
-enum Direction {
-    LEFT,
-    DOWN,
-    RIGHT,
-    UP,
-}
+pub fn wall_follower(maze: &Maze) -> Result<SolveResult, MazeError> {
+    let timer = Instant::now();
+
+    let start = get_start(maze)?;
+    let end = get_end(maze)?;
 
-pub fn wall_follower(maze: &[Vec<u8>]) -> Vec<(usize, usize)> {
-    let mut solved_path = Vec::with_capacity(maze.len() * maze[0].len());
-    let mut visited_square = vec![vec![false; maze[0].len()]; maze.len()];
+    let mut solved_path = Vec::with_capacity(maze.len());
+    let mut visited_square = vec![false; maze.len()];
 
-    let start = get_start(&maze);
-    let end = get_end(&maze);
+    // The walk jumps around when it pops a square to visit later,
+    //  the parents are used to get the actual path afterwards
+    let mut parents = vec![None; maze.len()];
 
-    let mut visit_later: Stack<(usize, usize)> = Stack::new();
+    let mut visit_later: Stack<Pos> = Stack::new();
+    let mut max_frontier = 0;
 
     // Solve it
     let mut current_path = start;
 
     while current_path != end {
-        let (x, y) = current_path;
-
-        // Can I go left ?
-        if path_left(&maze, x, y, &visited_square) {
-            check_visit_later(
-                &maze,
-                &visited_square,
-                current_path,
-                &mut visit_later,
-                Direction::LEFT,
-            );
-
-            visited_square[x][y] = true;
-
-            solved_path.push(current_path);
-            current_path = (x, y - 1);
-            continue;
-        }
-
-        // Can I go down ?
-        if path_down(&maze, x, y, &visited_square) {
-            check_visit_later(
-                &maze,
-                &visited_square,
-                current_path,
-                &mut visit_later,
-                Direction::DOWN,
-            );
-
-            visited_square[x][y] = true;
-
-            solved_path.push(current_path);
-            current_path = (x + 1, y);
-            continue;
-        }
-
-        // Can I go right?
-        if path_right(&maze, x, y, &visited_square) {
-            check_visit_later(
-                &maze,
-                &visited_square,
-                current_path,
-                &mut visit_later,
-                Direction::RIGHT,
-            );
-
-            visited_square[x][y] = true;
-
-            solved_path.push(current_path);
-            current_path = (x, y + 1);
-            continue;
-        }
+        // Can I go left? Down? Right? Up?
+        let next = LEFT_FIRST.iter().find_map(|&direction| {
+            unvisited_step(maze, &visited_square, current_path, direction)
+                .map(|next| (direction, next))
+        });
+
+        if let Some((direction, next)) = next {
+            // The other directions are visited later
+            for &other in LEFT_FIRST.iter().filter(|&&other| other != direction) {
+                if let Some(square) = unvisited_step(maze, &visited_square, current_path, other) {
+                    visit_later.push(square);
+                    set_parent(maze, &mut parents, square, current_path);
+                }
+            }
 
-        // Can I go up?
-        if path_up(&maze, x, y, &visited_square) {
-            check_visit_later(
-                &maze,
-                &visited_square,
-                current_path,
-                &mut visit_later,
-                Direction::UP,
-            );
+            max_frontier = max_frontier.max(visit_later.len());
 
-            visited_square[x][y] = true;
+            visited_square[maze.index(current_path)] = true;
 
             solved_path.push(current_path);
-            current_path = (x - 1, y);
+            set_parent(maze, &mut parents, next, current_path);
+            current_path = next;
             continue;
         }
 
         // Dead end, mark as visited
-        visited_square[x][y] = true;
+        visited_square[maze.index(current_path)] = true;
         solved_path.push(current_path);
 
         match visit_later.pop() {
             Some(path) => current_path = path,
-            None => break,
+            None => return Err(MazeError::Unreachable),
         }
     }
 
     solved_path.push(end);
 
-    solved_path
+    Ok(solve_result(
+        maze,
+        Some(backtrack(maze, &parents, start, end)?),
+        SolveStats {
+            nodes_expanded: solved_path.len(),
+            max_frontier,
+            elapsed: timer.elapsed(),
+        },
+        solved_path,
+    ))
 }
 
-fn check_visit_later(
-    maze: &[Vec<u8>],
-    visited_square: &[Vec<bool>],
-    current_path: (usize, usize),
-    visit_later: &mut Stack<(usize, usize)>,
-    direction: Direction,
-) {
-    let (x, y) = current_path;
-
-    if direction != Direction::LEFT && path_left(&maze, x, y, &visited_square) {
-        visit_later.push((x, y - 1));
-    }
-    if direction != Direction::DOWN && path_down(&maze, x, y, &visited_square) {
-        visit_later.push((x + 1, y));
-    }
-    if direction != Direction::RIGHT && path_right(&maze, x, y, &visited_square) {
-        visit_later.push((x, y + 1));
-    }
-    if direction != Direction::UP && path_up(&maze, x, y, &visited_square) {
-        visit_later.push((x - 1, y));
+// The first square to reach another one is its parent
+fn set_parent(maze: &Maze, parents: &mut [Option<Pos>], square: Pos, parent: Pos) {
+    let i = maze.index(square);
+
+    if parents[i].is_none() {
+        parents[i] = Some(parent);
     }
 }
 
 // ----------------------
 //  DEPTH FIRST SEARCH
 // ----------------------
-pub fn depth_first<S: BuildHasher>(
-    maze: &[Vec<u8>],
-    adj_list: &HashMap<(usize, usize), Vec<(usize, usize)>, S>,
-) -> Vec<(usize, usize)> {
-    let mut marked = vec![vec![false; maze[0].len()]; maze.len()];
+// The graph solvers work on any graph of the maze. On a compressed graph
+//  the path only goes through the nodes, and the cost is the one of the
+//  corridors in between
+pub fn depth_first(maze: &Maze, graph: &Graph) -> Result<SolveResult, MazeError> {
+    let timer = Instant::now();
+
+    let (maze_start, maze_end) = graph_ends(maze, graph)?;
+
+    let mut marked = vec![false; graph.node_count()];
+    let mut parents = vec![None; graph.node_count()];
+
+    // Each node is stacked along with the node it was reached from
+    //  and what it cost to get there
     let mut stack = Stack::new();
+    let mut max_frontier = 1;
 
-    let maze_start = get_start(&maze);
-    let maze_end = get_end(&maze);
+    let mut results = Vec::with_capacity(graph.node_count());
 
-    let mut results = Vec::with_capacity(adj_list.len());
+    stack.push((maze_start, None));
 
-    stack.push(maze_start);
+    while let Some((node, parent)) = stack.pop() {
+        if marked[node] {
+            continue;
+        }
 
-    while !stack.empty() {
-        let square = stack.pop().unwrap();
-        let nodes = adj_list.get(&square).unwrap();
-        if square == maze_end {
-            results.push(square);
-            break;
+        results.push(graph.pos(node));
+        marked[node] = true;
+        parents[node] = parent;
+
+        if node == maze_end {
+            return Ok(graph_result(
+                graph,
+                &parents,
+                maze_start,
+                maze_end,
+                SolveStats {
+                    nodes_expanded: results.len(),
+                    max_frontier,
+                    elapsed: timer.elapsed(),
+                },
+                results,
+            ));
         }
 
-        if !marked[square.0][square.1] {
-            results.push(square);
-            marked[square.0][square.1] = true;
+        let edges = graph.neighbors(node).iter().zip(graph.weights(node));
 
-            for node in nodes.iter().rev() {
-                stack.push(*node);
-            }
+        for (&next, &weight) in edges.rev() {
+            stack.push((next, Some((node, weight))));
         }
+
+        max_frontier = max_frontier.max(stack.len());
     }
 
-    results
+    Err(MazeError::Unreachable)
 }
 
 // ----------------------
 //  BREADTH FIRST SEARCH
 // ----------------------
-pub fn breadth_first<S: BuildHasher>(
-    maze: &[Vec<u8>],
-    adj_list: &HashMap<(usize, usize), Vec<(usize, usize)>, S>,
-) -> Vec<(usize, usize)> {
-    let mut marked = vec![vec![false; maze[0].len()]; maze.len()];
+pub fn breadth_first(maze: &Maze, graph: &Graph) -> Result<SolveResult, MazeError> {
+    let timer = Instant::now();
 
-    let mut queue = Queue::new();
+    let (maze_start, maze_end) = graph_ends(maze, graph)?;
 
-    let mut results = Vec::with_capacity(adj_list.len());
+    let mut marked = vec![false; graph.node_count()];
+    let mut parents = vec![None; graph.node_count()];
 
-    let maze_start = get_start(&maze);
-    let maze_end = get_end(&maze);
+    let mut queue = Queue::new();
+    let mut max_frontier = 1;
 
-    queue.enqueue(maze_start);
-    marked[maze_start.0][maze_start.1] = true;
+    let mut results = Vec::with_capacity(graph.node_count());
 
-    while !queue.empty() {
-        let square = queue.dequeue();
-        let nodes = adj_list.get(&square).unwrap();
+    queue.enqueue(maze_start);
+    marked[maze_start] = true;
 
-        results.push(square);
+    while let Some(node) = queue.dequeue() {
+        results.push(graph.pos(node));
 
         // Stop if we reached the end
-        if square == maze_end {
-            break;
+        if node == maze_end {
+            return Ok(graph_result(
+                graph,
+                &parents,
+                maze_start,
+                maze_end,
+                SolveStats {
+                    nodes_expanded: results.len(),
+                    max_frontier,
+                    elapsed: timer.elapsed(),
+                },
+                results,
+            ));
         }
 
-        for node in nodes.iter() {
+        for (&next, &weight) in graph.neighbors(node).iter().zip(graph.weights(node)) {
             // If they weren't visited, visit them
-            if !marked[node.0][node.1] {
-                queue.enqueue(*node);
-                marked[node.0][node.1] = true;
+            if !marked[next] {
+                queue.enqueue(next);
+                parents[next] = Some((node, weight));
+                marked[next] = true;
             }
         }
         // After the whole layer has been visited,
         //  visit the next layer or a node from same layer
+
+        max_frontier = max_frontier.max(queue.len());
     }
 
-    results
+    Err(MazeError::Unreachable)
 }
 
 // -----------------------------------
 //  BACKTRACKING BREADTH FIRST SEARCH
 // -----------------------------------
-pub fn backtracking_breadth_first<S: BuildHasher>(
-    maze: &[Vec<u8>],
-    adj_list: &HashMap<(usize, usize), Vec<(usize, usize)>, S>,
-) -> Vec<(usize, usize)> {
-    let mut marked = vec![vec![false; maze[0].len()]; maze.len()];
+pub fn backtracking_breadth_first(maze: &Maze, graph: &Graph) -> Result<SolveResult, MazeError> {
+    let timer = Instant::now();
 
-    let mut queue = Queue::new();
+    let (maze_start, maze_end) = graph_ends(maze, graph)?;
 
-    let maze_start = get_start(&maze);
-    let maze_end = get_end(&maze);
+    let mut marked = vec![false; graph.node_count()];
 
-    let mut parents: HashMap<(usize, usize), (usize, usize)> =
-        HashMap::with_capacity(adj_list.len());
+    let mut queue = Queue::new();
+    let mut max_frontier = 1;
+
+    let mut results = Vec::with_capacity(graph.node_count());
 
-    let mut results = Vec::with_capacity(adj_list.len());
-    results.push(maze_start);
+    let mut parents: HashMap<usize, (usize, u64)> = HashMap::with_capacity(graph.node_count());
 
     queue.enqueue(maze_start);
-    marked[maze_start.0][maze_start.1] = true;
+    marked[maze_start] = true;
 
-    while !queue.empty() {
-        let square = queue.dequeue();
-        let nodes = adj_list.get(&square).unwrap();
+    while let Some(node) = queue.dequeue() {
+        results.push(graph.pos(node));
 
         // Stop if we reached the end
-        if square == maze_end {
+        if node == maze_end {
             break;
         }
 
-        for node in nodes.iter() {
+        for (&next, &weight) in graph.neighbors(node).iter().zip(graph.weights(node)) {
             // If they weren't visited, visit them
-            if !marked[node.0][node.1] {
-                queue.enqueue(*node);
-                parents.insert(*node, square);
-                marked[node.0][node.1] = true;
+            if !marked[next] {
+                queue.enqueue(next);
+                parents.insert(next, (node, weight));
+                marked[next] = true;
             }
         }
         // After the whole layer has been visited,
         //  visit the next layer or a node from same layer
+
+        max_frontier = max_frontier.max(queue.len());
     }
 
     // We now backtrack from the end to the beginning
     //  to get the best path
+    let mut path = Vec::new();
+    let mut cost = 0;
     let mut current = maze_end;
 
     while current != maze_start {
-        results.push(current);
-        current = *parents.get(&current).unwrap();
+        path.push(graph.pos(current));
+        current = match parents.get(&current) {
+            Some(&(parent, weight)) => {
+                cost += weight;
+                parent
+            }
+            None => return Err(MazeError::Unreachable),
+        };
+    }
+
+    path.push(graph.pos(maze_start));
+    path.reverse();
+
+    Ok(SolveResult {
+        path: Some(path),
+        cost: Some(cost),
+        stats: SolveStats {
+            nodes_expanded: results.len(),
+            max_frontier,
+            elapsed: timer.elapsed(),
+        },
+        explored: results,
+    })
+}
+
+// The nodes of the start and the end of the maze
+fn graph_ends(maze: &Maze, graph: &Graph) -> Result<(usize, usize), MazeError> {
+    let start = get_start(maze)?;
+    let end = get_end(maze)?;
+
+    match (graph.id(start), graph.id(end)) {
+        (Some(start), Some(end)) => Ok((start, end)),
+        // The graph wasn't made from this maze
+        _ => Err(MazeError::Unreachable),
+    }
+}
+
+// Same as backtrack, on nodes. Each node keeps its parent and what
+//  it cost to come from it, the end is always reached here
+fn graph_result(
+    graph: &Graph,
+    parents: &[Option<(usize, u64)>],
+    start: usize,
+    end: usize,
+    stats: SolveStats,
+    explored: Vec<Pos>,
+) -> SolveResult {
+    let mut path = vec![graph.pos(end)];
+    let mut cost = 0;
+    let mut current = end;
+
+    while current != start {
+        let (parent, weight) = parents[current].expect("reached nodes have a parent");
+
+        cost += weight;
+        current = parent;
+        path.push(graph.pos(current));
+    }
+
+    path.reverse();
+
+    SolveResult {
+        path: Some(path),
+        explored,
+        cost: Some(cost),
+        stats,
+    }
+}
+
+// --------
+//  A STAR
+// --------
+// Estimates how far a square is from the end, the path is only optimal
+//  if it never overestimates. None of these do on a 4-connected grid
+//  where every square costs at least 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heuristic {
+    Manhattan,
+    Euclidean,
+    Chebyshev,
+    // Always 0, the same as Dijkstra
+    Zero,
+}
+
+impl Heuristic {
+    pub fn distance(self, (x1, y1): Pos, (x2, y2): Pos) -> f64 {
+        let dx = (x1 as f64 - x2 as f64).abs();
+        let dy = (y1 as f64 - y2 as f64).abs();
+
+        match self {
+            Heuristic::Manhattan => dx + dy,
+            Heuristic::Euclidean => (dx * dx + dy * dy).sqrt(),
+            Heuristic::Chebyshev => dx.max(dy),
+            Heuristic::Zero => 0.0,
+        }
+    }
+}
+
+// A square waiting to be expanded, the heap pops the smallest estimate first
+struct Candidate<T> {
+    estimate: f64,
+    cost: u64,
+    square: T,
+}
+
+impl<T> PartialEq for Candidate<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Candidate<T> {}
+
+impl<T> PartialOrd for Candidate<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Candidate<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, BinaryHeap is a max-heap.
+        //  On a tie, the square furthest from the start goes first
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+pub fn a_star(maze: &Maze, heuristic: Heuristic) -> Result<SolveResult, MazeError> {
+    let timer = Instant::now();
+
+    let maze_start = get_start(maze)?;
+    let maze_end = get_end(maze)?;
+
+    // Cost of the best known path from the start to each square
+    let mut costs = vec![u64::MAX; maze.len()];
+    let mut closed = vec![false; maze.len()];
+    let mut parents = vec![None; maze.len()];
+
+    let mut heap = BinaryHeap::new();
+    let mut max_frontier = 1;
+
+    let mut results = Vec::new();
+
+    costs[maze.index(maze_start)] = 0;
+    heap.push(Candidate {
+        estimate: heuristic.distance(maze_start, maze_end),
+        cost: 0,
+        square: maze_start,
+    });
+
+    while let Some(Candidate { cost, square, .. }) = heap.pop() {
+        // The same square can be pushed more than once,
+        //  only the cheapest one counts
+        if closed[maze.index(square)] {
+            continue;
+        }
+
+        closed[maze.index(square)] = true;
+        results.push(square);
+
+        if square == maze_end {
+            return Ok(solve_result(
+                maze,
+                Some(backtrack(maze, &parents, maze_start, maze_end)?),
+                SolveStats {
+                    nodes_expanded: results.len(),
+                    max_frontier,
+                    elapsed: timer.elapsed(),
+                },
+                results,
+            ));
+        }
+
+        for node in maze.neighbors(square) {
+            let i = maze.index(node);
+            let new_cost = cost + u64::from(maze.cost(node));
+
+            if !closed[i] && new_cost < costs[i] {
+                costs[i] = new_cost;
+                parents[i] = Some(square);
+
+                heap.push(Candidate {
+                    estimate: new_cost as f64 + heuristic.distance(node, maze_end),
+                    cost: new_cost,
+                    square: node,
+                });
+            }
+        }
+
+        max_frontier = max_frontier.max(heap.len());
     }
 
-    results
+    Err(MazeError::Unreachable)
+}
+
+// ----------
+//  DIJKSTRA
+// ----------
+// A* without a heuristic, the path has the smallest total cost
+//  on a weighted maze
+pub fn dijkstra(maze: &Maze) -> Result<SolveResult, MazeError> {
+    a_star(maze, Heuristic::Zero)
+}
+
+// Dijkstra on a graph of the maze, it gives the cheapest path
+//  on a compressed graph where the breadth first search doesn't
+pub fn graph_dijkstra(maze: &Maze, graph: &Graph) -> Result<SolveResult, MazeError> {
+    let timer = Instant::now();
+
+    let (maze_start, maze_end) = graph_ends(maze, graph)?;
+
+    let mut costs = vec![u64::MAX; graph.node_count()];
+    let mut closed = vec![false; graph.node_count()];
+    let mut parents = vec![None; graph.node_count()];
+
+    let mut heap = BinaryHeap::new();
+    let mut max_frontier = 1;
+
+    let mut results = Vec::new();
+
+    costs[maze_start] = 0;
+    heap.push(Candidate {
+        estimate: 0.0,
+        cost: 0,
+        square: maze_start,
+    });
+
+    while let Some(Candidate { cost, square, .. }) = heap.pop() {
+        if closed[square] {
+            continue;
+        }
+
+        closed[square] = true;
+        results.push(graph.pos(square));
+
+        if square == maze_end {
+            return Ok(graph_result(
+                graph,
+                &parents,
+                maze_start,
+                maze_end,
+                SolveStats {
+                    nodes_expanded: results.len(),
+                    max_frontier,
+                    elapsed: timer.elapsed(),
+                },
+                results,
+            ));
+        }
+
+        for (&next, &weight) in graph.neighbors(square).iter().zip(graph.weights(square)) {
+            let new_cost = cost + weight;
+
+            if !closed[next] && new_cost < costs[next] {
+                costs[next] = new_cost;
+                parents[next] = Some((square, weight));
+
+                heap.push(Candidate {
+                    estimate: new_cost as f64,
+                    cost: new_cost,
+                    square: next,
+                });
+            }
+        }
+
+        max_frontier = max_frontier.max(heap.len());
+    }
+
+    Err(MazeError::Unreachable)
+}
+
+// ------------------------------------
+//  BIDIRECTIONAL BREADTH FIRST SEARCH
+// ------------------------------------
+// Where the search from the start and the search from the end met
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Meeting {
+    // Reached by both searches
+    pub square: Pos,
+    // How many squares each queue still had when they met
+    pub forward_frontier: usize,
+    pub backward_frontier: usize,
+}
+
+// Searches from the start and from the end at the same time,
+//  a whole layer of the smaller side is visited each turn
+pub fn bidirectional_breadth_first(maze: &Maze) -> Result<(SolveResult, Meeting), MazeError> {
+    let timer = Instant::now();
+
+    let maze_start = get_start(maze)?;
+    let maze_end = get_end(maze)?;
+
+    // Index 0 is the search from the start, 1 the one from the end
+    let mut distances = [vec![usize::MAX; maze.len()], vec![usize::MAX; maze.len()]];
+    let mut parents = [vec![None; maze.len()], vec![None; maze.len()]];
+    let mut queues = [Queue::new(), Queue::new()];
+
+    let mut max_frontier = 2;
+    let mut results = Vec::new();
+
+    for (side, &square) in [maze_start, maze_end].iter().enumerate() {
+        distances[side][maze.index(square)] = 0;
+        queues[side].enqueue(square);
+    }
+
+    // Best (length, square on this side, square on the other side, side) found so far
+    let mut best: Option<(usize, Pos, Pos, usize)> = if maze_start == maze_end {
+        Some((0, maze_start, maze_start, 0))
+    } else {
+        None
+    };
+
+    while best.is_none() && !queues[0].empty() && !queues[1].empty() {
+        let side = if queues[0].len() <= queues[1].len() {
+            0
+        } else {
+            1
+        };
+        let other = 1 - side;
+
+        // The whole layer is visited, the first meeting
+        //  isn't always the shortest one
+        let layer_len = queues[side].len();
+        let layer: Vec<Pos> = queues[side].by_ref().take(layer_len).collect();
+
+        for square in layer {
+            let distance = distances[side][maze.index(square)];
+
+            results.push(square);
+
+            for node in maze.neighbors(square) {
+                let i = maze.index(node);
+
+                if distances[other][i] != usize::MAX {
+                    let length = distance + 1 + distances[other][i];
+
+                    if best.map_or(true, |(best_length, ..)| length < best_length) {
+                        best = Some((length, square, node, side));
+                    }
+                }
+
+                if distances[side][i] == usize::MAX {
+                    distances[side][i] = distance + 1;
+                    parents[side][i] = Some(square);
+                    queues[side].enqueue(node);
+                }
+            }
+        }
+
+        max_frontier = max_frontier.max(queues[0].len() + queues[1].len());
+    }
+
+    let (_, near, far, side) = best.ok_or(MazeError::Unreachable)?;
+
+    // Both halves go through the meeting square, `far` was reached by the
+    //  other side first so that's where the searches touched
+    let (forward_last, backward_first) = if side == 0 { (near, far) } else { (far, near) };
+
+    let mut path = backtrack(maze, &parents[0], maze_start, forward_last)?;
+
+    if backward_first != forward_last {
+        let mut current = backward_first;
+        path.push(current);
+
+        while current != maze_end {
+            current = parents[1][maze.index(current)].ok_or(MazeError::Unreachable)?;
+            path.push(current);
+        }
+    }
+
+    let meeting = Meeting {
+        square: far,
+        forward_frontier: queues[0].len(),
+        backward_frontier: queues[1].len(),
+    };
+
+    let result = solve_result(
+        maze,
+        Some(path),
+        SolveStats {
+            nodes_expanded: results.len(),
+            max_frontier,
+            elapsed: timer.elapsed(),
+        },
+        results,
+    );
+
+    Ok((result, meeting))
+}
+
+// ----------------
+//  DISTANCE FIELD
+// ----------------
+// A breadth first search that doesn't stop at the end: the steps from
+//  `source` to every square, by maze.index. None for the walls and for
+//  the open squares that can't be reached
+pub fn distance_map(maze: &Maze, source: Pos) -> Result<Vec<Option<usize>>, MazeError> {
+    if !maze.is_open(source) {
+        return Err(MazeError::BlockedSquare(source));
+    }
+
+    let mut distances = vec![None; maze.len()];
+    let mut queue = Queue::with_capacity(maze.len());
+
+    distances[maze.index(source)] = Some(0);
+    queue.enqueue((source, 0));
+
+    while let Some((square, distance)) = queue.dequeue() {
+        for next in maze.neighbors(square) {
+            if distances[maze.index(next)].is_none() {
+                distances[maze.index(next)] = Some(distance + 1);
+                queue.enqueue((next, distance + 1));
+            }
+        }
+    }
+
+    Ok(distances)
+}
+
+pub fn distance_map_from_start(maze: &Maze) -> Result<Vec<Option<usize>>, MazeError> {
+    distance_map(maze, get_start(maze)?)
 }
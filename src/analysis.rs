@@ -0,0 +1,227 @@
+use crate::algorithms::breadth_first;
+use crate::data_structures::Queue;
+use crate::{json, Graph, Maze, Pos};
+
+// How a maze is shaped, to judge how hard it is. Squares are counted
+//  by how many open squares are next to them: 1 is a dead end (the openings
+//  on the borders usually are), 2 is part of a corridor, 3 or 4 a junction
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    pub width: usize,
+    pub height: usize,
+    pub open_squares: usize,
+    pub dead_ends: usize,
+    pub junctions: usize,
+    // Runs of squares between two dead ends or junctions,
+    //  a loop with neither counts as one
+    pub corridors: usize,
+    // Groups of open squares that can't reach each other
+    pub components: usize,
+    // Independent loops: edges - squares + components
+    pub cyclomatic_number: usize,
+    // One component and no loop, so exactly one path between two squares
+    pub perfect: bool,
+    // Squares in the shortest solution, None without a start,
+    //  an end or a way between them
+    pub solution_length: Option<usize>,
+    // Solution length over open squares
+    pub solution_ratio: Option<f64>,
+    // In steps from one end to the other
+    pub longest_corridor: usize,
+    // Mean squares in a branch leading to a dead end. Mazes with a lot of
+    //  river have few but long dead ends, they're harder to rule out
+    pub river: f64,
+}
+
+impl Analysis {
+    pub fn to_json(&self) -> String {
+        json::pretty_object(&[
+            ("width", self.width.to_string()),
+            ("height", self.height.to_string()),
+            ("open_squares", self.open_squares.to_string()),
+            ("dead_ends", self.dead_ends.to_string()),
+            ("junctions", self.junctions.to_string()),
+            ("corridors", self.corridors.to_string()),
+            ("components", self.components.to_string()),
+            ("cyclomatic_number", self.cyclomatic_number.to_string()),
+            ("perfect", self.perfect.to_string()),
+            ("solution_length", json::optional(self.solution_length)),
+            ("solution_ratio", json::optional(self.solution_ratio)),
+            ("longest_corridor", self.longest_corridor.to_string()),
+            ("river", self.river.to_string()),
+        ])
+    }
+}
+
+pub fn analyze(maze: &Maze) -> Analysis {
+    let degrees: Vec<usize> = (0..maze.len())
+        .map(|i| {
+            let square = maze.pos(i);
+
+            if maze.is_open(square) {
+                maze.neighbors(square).count()
+            } else {
+                0
+            }
+        })
+        .collect();
+
+    let open_squares = maze.open_squares().count();
+    let edges = degrees.iter().sum::<usize>() / 2;
+    let components = count_components(maze);
+
+    let corridors = Corridors::walk(maze, &degrees);
+
+    let solution_length = maze.start().and(maze.end()).ok().and_then(|_| {
+        breadth_first(maze, &Graph::from_maze(maze))
+            .ok()
+            .and_then(|result| result.path)
+            .map(|path| path.len())
+    });
+
+    let cyclomatic_number = (edges + components).saturating_sub(open_squares);
+
+    Analysis {
+        width: maze.width(),
+        height: maze.height(),
+        open_squares,
+        dead_ends: degrees.iter().filter(|&&degree| degree == 1).count(),
+        junctions: degrees.iter().filter(|&&degree| degree >= 3).count(),
+        corridors: corridors.count,
+        components,
+        cyclomatic_number,
+        perfect: components == 1 && cyclomatic_number == 0,
+        solution_length,
+        solution_ratio: solution_length
+            .filter(|_| open_squares > 0)
+            .map(|length| length as f64 / open_squares as f64),
+        longest_corridor: corridors.longest,
+        river: corridors.river(),
+    }
+}
+
+// Flood fills from every open square that wasn't reached yet
+fn count_components(maze: &Maze) -> usize {
+    let mut reached = vec![false; maze.len()];
+    let mut queue = Queue::new();
+    let mut components = 0;
+
+    for square in maze.open_squares() {
+        if reached[maze.index(square)] {
+            continue;
+        }
+
+        components += 1;
+        reached[maze.index(square)] = true;
+        queue.enqueue(square);
+
+        while let Some(current) = queue.dequeue() {
+            for next in maze.neighbors(current) {
+                if !reached[maze.index(next)] {
+                    reached[maze.index(next)] = true;
+                    queue.enqueue(next);
+                }
+            }
+        }
+    }
+
+    components
+}
+
+struct Corridors {
+    count: usize,
+    longest: usize,
+    // Squares in every branch that ends on a dead end, and how many there are
+    dead_end_squares: usize,
+    dead_end_branches: usize,
+}
+
+impl Corridors {
+    // From every dead end and junction, along each of its corridors
+    fn walk(maze: &Maze, degrees: &[usize]) -> Self {
+        let mut corridors = Corridors {
+            count: 0,
+            longest: 0,
+            dead_end_squares: 0,
+            dead_end_branches: 0,
+        };
+
+        let is_end = |square: Pos| degrees[maze.index(square)] != 2;
+        let mut in_corridor = vec![false; maze.len()];
+
+        for from in maze.open_squares().filter(|&square| is_end(square)) {
+            for first in maze.neighbors(from) {
+                // Walks until the square isn't part of a corridor
+                let (mut previous, mut current, mut steps) = (from, first, 1);
+
+                while !is_end(current) {
+                    in_corridor[maze.index(current)] = true;
+
+                    let next = maze
+                        .neighbors(current)
+                        .find(|&next| next != previous)
+                        .unwrap_or(previous);
+
+                    previous = current;
+                    current = next;
+                    steps += 1;
+                }
+
+                // Seen from both ends, it's counted from the smaller one
+                let here = (maze.index(from), maze.index(first));
+                let there = (maze.index(current), maze.index(previous));
+
+                if here <= there {
+                    corridors.count += 1;
+                    corridors.longest = corridors.longest.max(steps);
+                }
+
+                // The squares of the corridor and the dead end at its end
+                if degrees[maze.index(current)] == 1 && degrees[maze.index(from)] != 1 {
+                    corridors.dead_end_squares += steps;
+                    corridors.dead_end_branches += 1;
+                }
+            }
+        }
+
+        // What's left are loops of corridor squares
+        for square in maze.open_squares() {
+            if in_corridor[maze.index(square)] || is_end(square) {
+                continue;
+            }
+
+            let mut steps = 0;
+            let (mut previous, mut current) = (square, square);
+
+            loop {
+                in_corridor[maze.index(current)] = true;
+                steps += 1;
+
+                let next = maze
+                    .neighbors(current)
+                    .find(|&next| next != previous && !in_corridor[maze.index(next)]);
+
+                match next {
+                    Some(next) => {
+                        previous = current;
+                        current = next;
+                    }
+                    None => break,
+                }
+            }
+
+            corridors.count += 1;
+            corridors.longest = corridors.longest.max(steps);
+        }
+
+        corridors
+    }
+
+    fn river(&self) -> f64 {
+        if self.dead_end_branches == 0 {
+            0.0
+        } else {
+            self.dead_end_squares as f64 / self.dead_end_branches as f64
+        }
+    }
+}
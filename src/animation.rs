@@ -0,0 +1,227 @@
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::error::{EncodingError, ImageFormatHint, UnsupportedError};
+use image::{Delay, Frame, ImageError, ImageFormat, Rgb, Rgba, RgbaImage};
+
+use crate::{Maze, MazeError, Pos, SolveResult};
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+// What an animation looks like. Every frame adds `steps_per_frame` squares
+//  of the exploration order, then the last frame shows the path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationStyle {
+    pub steps_per_frame: usize,
+    // How long each frame stays, in milliseconds
+    pub frame_delay: u32,
+    // How long the path stays before the animation starts again
+    pub final_delay: u32,
+    // Pixels per square, on both axes
+    pub scale: u32,
+    pub wall: Rgb<u8>,
+    pub open: Rgb<u8>,
+    // Squares next to a visited square, not visited yet. Not the frontier
+    //  of the solver, only its exploration order is known
+    pub neighbor: Rgb<u8>,
+    pub visited: Rgb<u8>,
+    pub path: Rgb<u8>,
+}
+
+impl Default for AnimationStyle {
+    fn default() -> Self {
+        Self {
+            steps_per_frame: 1,
+            frame_delay: 40,
+            final_delay: 2000,
+            scale: 4,
+            wall: Rgb([0, 0, 0]),
+            open: Rgb([255, 255, 255]),
+            neighbor: Rgb([255, 200, 0]),
+            visited: Rgb([150, 190, 255]),
+            path: Rgb([255, 0, 0]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationFormat {
+    Gif,
+    // Animated PNG, more colors than a GIF but not every viewer plays it
+    Apng,
+}
+
+impl AnimationFormat {
+    // .gif, or .png/.apng
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "gif" => Some(AnimationFormat::Gif),
+            "png" | "apng" => Some(AnimationFormat::Apng),
+            _ => None,
+        }
+    }
+}
+
+// Where each square is at in the search
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Unseen,
+    Neighbor,
+    Visited,
+}
+
+// Number of frames write_animation writes: the empty maze,
+//  the exploration, then the path
+pub fn frame_count(result: &SolveResult, style: &AnimationStyle) -> usize {
+    let steps = style.steps_per_frame.max(1);
+
+    1 + result.explored.len().div_ceil(steps) + 1
+}
+
+// Calls `frame` with every frame of the animation and how long it stays.
+//  The explored squares are drawn along with their neighbors not explored yet
+pub fn for_each_frame<F>(
+    maze: &Maze,
+    result: &SolveResult,
+    style: &AnimationStyle,
+    mut frame: F,
+) -> Result<(), MazeError>
+where
+    F: FnMut(&RgbaImage, u32) -> Result<(), MazeError>,
+{
+    let scale = style.scale.max(1);
+    let mut img = RgbaImage::new(maze.width() as u32 * scale, maze.height() as u32 * scale);
+
+    let paint = |img: &mut RgbaImage, (line, col): Pos, color: Rgb<u8>| {
+        let Rgb([r, g, b]) = color;
+
+        for y in 0..scale {
+            for x in 0..scale {
+                img.put_pixel(
+                    col as u32 * scale + x,
+                    line as u32 * scale + y,
+                    Rgba([r, g, b, 255]),
+                );
+            }
+        }
+    };
+
+    for line in 0..maze.height() {
+        for col in 0..maze.width() {
+            let color = if maze.is_open((line, col)) {
+                style.open
+            } else {
+                style.wall
+            };
+
+            paint(&mut img, (line, col), color);
+        }
+    }
+
+    frame(&img, style.frame_delay)?;
+
+    let mut states = vec![State::Unseen; maze.len()];
+
+    for steps in result.explored.chunks(style.steps_per_frame.max(1)) {
+        for &square in steps {
+            states[maze.index(square)] = State::Visited;
+            paint(&mut img, square, style.visited);
+
+            for next in maze.neighbors(square) {
+                if states[maze.index(next)] == State::Unseen {
+                    states[maze.index(next)] = State::Neighbor;
+                    paint(&mut img, next, style.neighbor);
+                }
+            }
+        }
+
+        frame(&img, style.frame_delay)?;
+    }
+
+    for &square in result.path.iter().flatten() {
+        paint(&mut img, square, style.path);
+    }
+
+    frame(&img, style.final_delay)
+}
+
+// Writes the whole animation, it plays forever
+pub fn write_animation<W: Write>(
+    maze: &Maze,
+    result: &SolveResult,
+    style: &AnimationStyle,
+    format: AnimationFormat,
+    writer: W,
+) -> Result<(), MazeError> {
+    match format {
+        AnimationFormat::Gif => {
+            let mut encoder = GifEncoder::new(writer);
+            encoder
+                .set_repeat(Repeat::Infinite)
+                .map_err(MazeError::ImageEncode)?;
+
+            for_each_frame(maze, result, style, |img, delay| {
+                let delay = Delay::from_numer_denom_ms(delay, 1);
+
+                encoder
+                    .encode_frame(Frame::from_parts(img.clone(), 0, 0, delay))
+                    .map_err(MazeError::ImageEncode)
+            })
+        }
+        AnimationFormat::Apng => {
+            let scale = style.scale.max(1);
+            let (width, height) = (maze.width() as u32 * scale, maze.height() as u32 * scale);
+
+            let mut encoder = png::Encoder::new(writer, width, height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder
+                .set_animated(frame_count(result, style) as u32, 0)
+                .map_err(png_error)?;
+
+            let mut writer = encoder.write_header().map_err(png_error)?;
+
+            for_each_frame(maze, result, style, |img, delay| {
+                // In 1/1000th of a second, as long as it fits
+                let delay = delay.min(u32::from(u16::MAX)) as u16;
+
+                writer.set_frame_delay(delay, 1000).map_err(png_error)?;
+                writer.write_image_data(img.as_raw()).map_err(png_error)
+            })?;
+
+            writer.finish().map_err(png_error)
+        }
+    }
+}
+
+// The format is guessed from the extension, see AnimationFormat::from_path
+pub fn save_animation(
+    maze: &Maze,
+    result: &SolveResult,
+    style: &AnimationStyle,
+    output: &str,
+) -> Result<(), MazeError> {
+    let format = match AnimationFormat::from_path(output) {
+        Some(format) => format,
+        None => {
+            let hint = ImageFormatHint::from(Path::new(output));
+
+            return Err(MazeError::ImageEncode(ImageError::Unsupported(
+                UnsupportedError::from(hint),
+            )));
+        }
+    };
+
+    let file = File::create(output).map_err(MazeError::Io)?;
+
+    write_animation(maze, result, style, format, BufWriter::new(file))
+}
+
+fn png_error(error: png::EncodingError) -> MazeError {
+    MazeError::ImageEncode(ImageError::Encoding(EncodingError::new(
+        ImageFormat::Png.into(),
+        error,
+    )))
+}
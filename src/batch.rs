@@ -0,0 +1,431 @@
+use crate::geometry::output_maze_solution_scaled;
+use crate::input::{self, InputConfig, LoadedMaze};
+use crate::render::{output_maze_solution_with_style, RenderStyle};
+use crate::text::{self, Charset};
+use crate::{json, output_maze_solution, CellGeometry, MazeError, Pos, Registry};
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+// What a directory is searched for
+pub const MAZE_EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "bmp", "gif", "tif", "tiff", "txt"];
+
+// Where the solutions go, `<name>_<solver>.<extension>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOutput {
+    // Only the summary
+    None,
+    NextToInput,
+    // A file under `root` goes to the same place under `into`
+    Mirror { root: PathBuf, into: PathBuf },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchOptions {
+    // Names in the registry
+    pub solvers: Vec<String>,
+    // 0 for one per CPU core
+    pub threads: usize,
+    pub output: BatchOutput,
+    // How every maze is read
+    pub input: InputConfig,
+}
+
+// One maze solved by one solver
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchEntry {
+    pub input: PathBuf,
+    pub solver: String,
+    // Squares from the start to the end, both included
+    pub path_length: Option<usize>,
+    pub cost: Option<u64>,
+    pub explored: usize,
+    pub elapsed: Duration,
+    pub output: Option<PathBuf>,
+    // Why the maze couldn't be loaded, solved or written
+    pub error: Option<String>,
+}
+
+// --------------
+// Finding inputs
+// --------------
+// Every maze under a directory, or matching a pattern that can use `*`
+//  and `?` in names and `**` for any number of directories. Solutions
+//  written by an earlier run, with any of the solvers, aren't mazes to solve
+pub fn find_inputs(
+    pattern: &str,
+    registry: &Registry,
+    options: &BatchOptions,
+) -> Result<Vec<PathBuf>, MazeError> {
+    let path = Path::new(pattern);
+
+    // Doesn't exist before the first run
+    let skip = match &options.output {
+        BatchOutput::Mirror { into, .. } => fs::canonicalize(into).ok(),
+        _ => None,
+    };
+
+    let mut files = Vec::new();
+
+    if path.is_dir() {
+        walk(path, skip.as_deref(), &mut files)?;
+    } else {
+        let root = pattern_root(pattern);
+        let parts = pattern_parts(pattern, &root);
+
+        walk(&root, skip.as_deref(), &mut files)?;
+        files.retain(|file| {
+            let relative = file.strip_prefix(&root).unwrap_or(file);
+            let names: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+
+            glob_match(&parts, &names)
+        });
+    }
+
+    let solvers = registry.names();
+
+    files.retain(|file| is_maze_file(file) && !is_solution(file, &solvers));
+
+    files.sort();
+
+    Ok(files)
+}
+
+// The directories of the pattern before the first wildcard
+pub fn pattern_root(pattern: &str) -> PathBuf {
+    let path = Path::new(pattern);
+
+    if path.is_dir() {
+        return path.to_path_buf();
+    }
+
+    let mut root = PathBuf::new();
+
+    for component in path.components() {
+        let name = component.as_os_str().to_string_lossy();
+
+        if name.contains(['*', '?']) {
+            break;
+        }
+
+        root.push(component);
+    }
+
+    // A plain file name, its directory is the root
+    if root == path {
+        root.pop();
+    }
+
+    if root.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        root
+    }
+}
+
+// What's left of the pattern after the root, one part per directory
+fn pattern_parts<'a>(pattern: &'a str, root: &Path) -> Vec<&'a str> {
+    let skip = root
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .count();
+
+    Path::new(pattern)
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .skip(skip)
+        .filter_map(|component| component.as_os_str().to_str())
+        .collect()
+}
+
+// Everything under `dir` but the `skip` directory
+fn walk(dir: &Path, skip: Option<&Path>, files: &mut Vec<PathBuf>) -> Result<(), MazeError> {
+    let entries = fs::read_dir(dir).map_err(MazeError::Io)?;
+
+    for entry in entries {
+        let path = entry.map_err(MazeError::Io)?.path();
+
+        if path.is_dir() {
+            if skip.is_none() || fs::canonicalize(&path).ok().as_deref() != skip {
+                walk(&path, skip, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn is_maze_file(path: &Path) -> bool {
+    extension(path).is_some_and(|extension| MAZE_EXTENSIONS.contains(&extension.as_str()))
+}
+
+// `<name>_<solver>.<extension>` next to `<name>.<extension>`, see output_path
+fn is_solution(path: &Path, solvers: &[&str]) -> bool {
+    let (stem, extension) = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(extension)) => (stem.to_string_lossy(), extension.to_string_lossy()),
+        _ => return false,
+    };
+
+    solvers.iter().any(|solver| {
+        stem.strip_suffix(solver)
+            .and_then(|name| name.strip_suffix('_'))
+            .is_some_and(|name| {
+                path.with_file_name(format!("{}.{}", name, extension))
+                    .is_file()
+            })
+    })
+}
+
+// `**` matches any number of directories
+fn glob_match(parts: &[&str], names: &[String]) -> bool {
+    match (parts.split_first(), names.split_first()) {
+        (None, None) => true,
+        (Some((&"**", rest)), _) => {
+            glob_match(rest, names) || (!names.is_empty() && glob_match(parts, &names[1..]))
+        }
+        (Some((part, rest)), Some((name, names))) => {
+            wildcard_match(part.as_bytes(), name.as_bytes()) && glob_match(rest, names)
+        }
+        _ => false,
+    }
+}
+
+// `*` matches anything, `?` one character
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            wildcard_match(rest, name) || (!name.is_empty() && wildcard_match(pattern, &name[1..]))
+        }
+        (Some((b'?', rest)), Some((_, name))) => wildcard_match(rest, name),
+        (Some((p, rest)), Some((n, name))) => p == n && wildcard_match(rest, name),
+        _ => false,
+    }
+}
+
+// -------
+// Solving
+// -------
+// Every input with every solver, spread over the threads. The entries
+//  are in the order of the inputs, then of the solvers
+pub fn solve_batch(
+    inputs: &[PathBuf],
+    registry: &Registry,
+    options: &BatchOptions,
+) -> Vec<BatchEntry> {
+    let threads = match options.threads {
+        0 => thread::available_parallelism().map_or(1, |threads| threads.get()),
+        threads => threads,
+    }
+    .min(inputs.len())
+    .max(1);
+
+    let next = AtomicUsize::new(0);
+    let solved = Mutex::new(Vec::with_capacity(inputs.len()));
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+
+                let input = match inputs.get(i) {
+                    Some(input) => input,
+                    None => break,
+                };
+
+                let entries = solve_file(input, registry, options);
+
+                solved.lock().unwrap().push((i, entries));
+            });
+        }
+    });
+
+    let mut solved = solved.into_inner().unwrap();
+    solved.sort_by_key(|&(i, _)| i);
+
+    solved
+        .into_iter()
+        .flat_map(|(_, entries)| entries)
+        .collect()
+}
+
+// An error loading the maze is given to every solver
+fn solve_file(input: &Path, registry: &Registry, options: &BatchOptions) -> Vec<BatchEntry> {
+    let entry = |solver: &str| BatchEntry {
+        input: input.to_path_buf(),
+        solver: solver.to_string(),
+        ..BatchEntry::default()
+    };
+
+    let loaded = input::load_maze(input, &options.input);
+
+    options
+        .solvers
+        .iter()
+        .map(|name| {
+            let mut entry = entry(name);
+
+            if let Err(e) = solve_one(&mut entry, &loaded, registry, options) {
+                entry.error = Some(e);
+            }
+
+            entry
+        })
+        .collect()
+}
+
+fn solve_one(
+    entry: &mut BatchEntry,
+    loaded: &Result<LoadedMaze, MazeError>,
+    registry: &Registry,
+    options: &BatchOptions,
+) -> Result<(), String> {
+    let loaded = loaded.as_ref().map_err(|e| e.to_string())?;
+    let maze = &loaded.maze;
+
+    let solver = registry
+        .get(&entry.solver)
+        .ok_or_else(|| format!("unknown solver `{}`", entry.solver))?;
+
+    let result = solver.solve(maze).map_err(|e| e.to_string())?;
+
+    entry.path_length = result.path.as_ref().map(|path| path.len());
+    entry.cost = result.cost;
+    entry.explored = result.explored.len();
+    entry.elapsed = result.stats.elapsed;
+
+    let output = match output_path(&entry.input, &entry.solver, &options.output) {
+        Some(output) => output,
+        None => return Ok(()),
+    };
+
+    let path: &[Pos] = result.path.as_deref().unwrap_or(&[]);
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let name = output.to_string_lossy();
+
+    // Drawn over the input when the squares can be traced back to its pixels
+    match (&loaded.image, loaded.geometry) {
+        (Some(img), Some(CellGeometry::PIXEL)) => output_maze_solution(img, path, &name),
+        (Some(img), Some(geometry)) => output_maze_solution_scaled(img, path, geometry, &name),
+        (Some(_), None) => {
+            output_maze_solution_with_style(maze, path, &[], &RenderStyle::default(), &name)
+        }
+        (None, _) => fs::write(&output, text::maze_to_text(maze, path, &Charset::default()))
+            .map_err(MazeError::Io),
+    }
+    .map_err(|e| e.to_string())?;
+
+    entry.output = Some(output);
+
+    Ok(())
+}
+
+fn output_path(input: &Path, solver: &str, output: &BatchOutput) -> Option<PathBuf> {
+    let dir = match output {
+        BatchOutput::None => return None,
+        BatchOutput::NextToInput => input.parent()?.to_path_buf(),
+        BatchOutput::Mirror { root, into } => {
+            let relative = input.strip_prefix(root).unwrap_or(input);
+            into.join(relative.parent()?)
+        }
+    };
+
+    let stem = input.file_stem()?.to_string_lossy();
+    let extension = input.extension()?.to_string_lossy();
+
+    Some(dir.join(format!("{}_{}.{}", stem, solver, extension)))
+}
+
+// -------
+// Summary
+// -------
+pub fn summary_csv(entries: &[BatchEntry]) -> String {
+    let mut csv = String::from("input,solver,path_length,cost,explored,elapsed_ms,output,error\n");
+
+    for entry in entries {
+        let fields = [
+            csv_field(&entry.input.to_string_lossy()),
+            csv_field(&entry.solver),
+            optional(entry.path_length),
+            optional(entry.cost),
+            entry.explored.to_string(),
+            format!("{:.3}", entry.elapsed.as_secs_f64() * 1000.0),
+            csv_field(
+                &entry
+                    .output
+                    .as_ref()
+                    .map_or_else(String::new, |output| output.to_string_lossy().into_owned()),
+            ),
+            csv_field(entry.error.as_deref().unwrap_or("")),
+        ];
+
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+// An array with one object per entry
+pub fn summary_json(entries: &[BatchEntry]) -> String {
+    let objects: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            let output = entry
+                .output
+                .as_ref()
+                .map(|output| json::string(&output.to_string_lossy()));
+
+            json::object(&[
+                ("input", json::string(&entry.input.to_string_lossy())),
+                ("solver", json::string(&entry.solver)),
+                ("path_length", json::optional(entry.path_length)),
+                ("cost", json::optional(entry.cost)),
+                ("explored", entry.explored.to_string()),
+                (
+                    "elapsed_ms",
+                    format!("{:.3}", entry.elapsed.as_secs_f64() * 1000.0),
+                ),
+                ("output", json::optional(output)),
+                (
+                    "error",
+                    json::optional(entry.error.as_deref().map(json::string)),
+                ),
+            ])
+        })
+        .collect();
+
+    format!("[\n  {}\n]\n", objects.join(",\n  "))
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(String::new, |value| value.to_string())
+}
+
+// Quoted when it has a comma, a quote or a new line
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase())
+}
@@ -1,3 +1,6 @@
+use std::collections::VecDeque;
+use std::iter::FromIterator;
+
 // STACK
 #[derive(Default)]
 pub struct Stack<T> {
@@ -20,32 +23,91 @@ impl<T> Stack<T> {
     pub fn empty(&self) -> bool {
         self.item.is_empty()
     }
+
+    pub fn len(&self) -> usize {
+        self.item.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.item.is_empty()
+    }
 }
 
 // QUEUE
+// A ring buffer, both ends are O(1)
 #[derive(Default)]
 pub struct Queue<T> {
-    item: Vec<T>,
+    item: VecDeque<T>,
 }
 
 impl<T> Queue<T> {
     pub fn new() -> Self {
-        Self { item: Vec::new() }
+        Self {
+            item: VecDeque::new(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            item: VecDeque::with_capacity(capacity),
+        }
     }
 
     pub fn enqueue(&mut self, x: T) {
-        self.item.push(x);
+        self.item.push_back(x);
     }
 
-    pub fn dequeue(&mut self) -> T {
-        if self.item.is_empty() {
-            panic!("Can't dequeue an empty queue!");
-        }
+    // None if the queue is empty
+    pub fn dequeue(&mut self) -> Option<T> {
+        self.item.pop_front()
+    }
 
-        self.item.remove(0)
+    // The next item to be dequeued
+    pub fn peek(&self) -> Option<&T> {
+        self.item.front()
     }
 
     pub fn empty(&self) -> bool {
         self.item.is_empty()
     }
+
+    pub fn len(&self) -> usize {
+        self.item.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.item.is_empty()
+    }
+
+    // From the first to be dequeued to the last
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.item.iter()
+    }
+}
+
+// Iterating dequeues every item
+impl<T> Iterator for Queue<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.dequeue()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
+}
+
+impl<T> Extend<T> for Queue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.item.extend(iter);
+    }
+}
+
+impl<T> FromIterator<T> for Queue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            item: iter.into_iter().collect(),
+        }
+    }
 }
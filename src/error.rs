@@ -0,0 +1,93 @@
+use std::fmt;
+use std::io;
+
+use image::ImageError;
+
+// Every fallible function of the library returns this error,
+//  it's up to the caller (e.g. the binary) to decide what to do with it
+#[derive(Debug)]
+pub enum MazeError {
+    // No image was given to the program
+    MissingArgument,
+    // No opening found on the top row or the left column
+    NoStart,
+    // No opening found on the bottom row or the right column
+    NoEnd,
+    // The end can't be reached from the start
+    Unreachable,
+    // The image couldn't be opened or decoded
+    ImageDecode(ImageError),
+    // The image couldn't be encoded or written to disk
+    ImageEncode(ImageError),
+    // Any other file (text mazes, reports, directories...) couldn't be
+    //  read or written
+    Io(io::Error),
+    // The maze has no rows or no columns
+    EmptyMaze,
+    // The square is a WALL or outside of the maze
+    BlockedSquare((usize, usize)),
+    // A square was given a cost of 0
+    ZeroCost((usize, usize)),
+    // A CellGeometry with walls or corridors 0 pixels thick
+    ZeroThickness,
+    // A row doesn't have the same length as the first one
+    RaggedRows {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    // A text maze is malformed, lines and columns start at 1
+    Parse {
+        line: usize,
+        column: usize,
+        reason: String,
+    },
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MazeError::MissingArgument => write!(f, "no image was given"),
+            MazeError::NoStart => write!(f, "start point not found"),
+            MazeError::NoEnd => write!(f, "ending point not found"),
+            MazeError::Unreachable => write!(f, "the end can't be reached from the start"),
+            MazeError::ImageDecode(e) => write!(f, "couldn't open the image: {}", e),
+            MazeError::ImageEncode(e) => write!(f, "couldn't save the image: {}", e),
+            MazeError::Io(e) => write!(f, "couldn't read or write the file: {}", e),
+            MazeError::EmptyMaze => write!(f, "the maze is empty"),
+            MazeError::BlockedSquare((line, col)) => {
+                write!(f, "square ({}, {}) isn't an open square", line, col)
+            }
+            MazeError::ZeroCost((line, col)) => {
+                write!(f, "square ({}, {}) has a cost of 0", line, col)
+            }
+            MazeError::ZeroThickness => {
+                write!(f, "walls and corridors must be at least a pixel thick")
+            }
+            MazeError::RaggedRows {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "row {} has {} columns, expected {}",
+                row, found, expected
+            ),
+            MazeError::Parse {
+                line,
+                column,
+                reason,
+            } => write!(f, "line {}, column {}: {}", line, column, reason),
+        }
+    }
+}
+
+impl std::error::Error for MazeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MazeError::ImageDecode(e) | MazeError::ImageEncode(e) => Some(e),
+            MazeError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,644 @@
+use crate::constants::{PATH, WALL};
+use crate::Maze;
+use crate::MazeError;
+
+use std::str::FromStr;
+
+// ---
+// RNG
+// ---
+// SplitMix64, small and good enough to carve mazes. The same seed
+//  always gives the same maze, on every platform
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    }
+
+    // A number in 0..n, n can't be 0
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn coin(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    pub fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+// ----------
+// Algorithms
+// ----------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    RecursiveBacktracker,
+    Prim,
+    Kruskal,
+    Wilson,
+    AldousBroder,
+    Eller,
+    HuntAndKill,
+    Sidewinder,
+    BinaryTree,
+    RecursiveDivision,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 10] = [
+        Algorithm::RecursiveBacktracker,
+        Algorithm::Prim,
+        Algorithm::Kruskal,
+        Algorithm::Wilson,
+        Algorithm::AldousBroder,
+        Algorithm::Eller,
+        Algorithm::HuntAndKill,
+        Algorithm::Sidewinder,
+        Algorithm::BinaryTree,
+        Algorithm::RecursiveDivision,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::RecursiveBacktracker => "recursive_backtracker",
+            Algorithm::Prim => "prim",
+            Algorithm::Kruskal => "kruskal",
+            Algorithm::Wilson => "wilson",
+            Algorithm::AldousBroder => "aldous_broder",
+            Algorithm::Eller => "eller",
+            Algorithm::HuntAndKill => "hunt_and_kill",
+            Algorithm::Sidewinder => "sidewinder",
+            Algorithm::BinaryTree => "binary_tree",
+            Algorithm::RecursiveDivision => "recursive_division",
+        }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, ()> {
+        Algorithm::ALL
+            .iter()
+            .copied()
+            .find(|algorithm| algorithm.name() == name)
+            .ok_or(())
+    }
+}
+
+// A maze of `width` x `height` cells. Walls take a square too, so the maze
+//  is (2 * width + 1) x (2 * height + 1) squares, with the entrance on the
+//  top left and the exit on the bottom right
+pub fn generate(
+    algorithm: Algorithm,
+    width: usize,
+    height: usize,
+    seed: u64,
+) -> Result<Maze, MazeError> {
+    generate_with(algorithm, width, height, &mut Rng::new(seed))
+}
+
+pub fn generate_with(
+    algorithm: Algorithm,
+    width: usize,
+    height: usize,
+    rng: &mut Rng,
+) -> Result<Maze, MazeError> {
+    if width == 0 || height == 0 {
+        return Err(MazeError::EmptyMaze);
+    }
+
+    let mut grid = Grid::new(width, height);
+
+    match algorithm {
+        Algorithm::RecursiveBacktracker => recursive_backtracker(&mut grid, rng),
+        Algorithm::Prim => prim(&mut grid, rng),
+        Algorithm::Kruskal => kruskal(&mut grid, rng),
+        Algorithm::Wilson => wilson(&mut grid, rng),
+        Algorithm::AldousBroder => aldous_broder(&mut grid, rng),
+        Algorithm::Eller => eller(&mut grid, rng),
+        Algorithm::HuntAndKill => hunt_and_kill(&mut grid, rng),
+        Algorithm::Sidewinder => sidewinder(&mut grid, rng),
+        Algorithm::BinaryTree => binary_tree(&mut grid, rng),
+        Algorithm::RecursiveDivision => recursive_division(&mut grid, rng),
+    }
+
+    grid.into_maze()
+}
+
+// ----
+// Grid
+// ----
+// A cell, as (line, column), cell (r, c) is square (2r + 1, 2c + 1)
+type Cell = (usize, usize);
+
+struct Grid {
+    width: usize,
+    height: usize,
+    squares: Vec<u8>,
+}
+
+impl Grid {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            squares: vec![WALL; (2 * width + 1) * (2 * height + 1)],
+        }
+    }
+
+    fn set(&mut self, (line, col): (usize, usize), square: u8) {
+        let i = line * (2 * self.width + 1) + col;
+        self.squares[i] = square;
+    }
+
+    fn index(&self, (line, col): Cell) -> usize {
+        line * self.width + col
+    }
+
+    fn len(&self) -> usize {
+        self.width * self.height
+    }
+
+    // Opens both cells and the wall between them
+    fn connect(&mut self, (r1, c1): Cell, (r2, c2): Cell) {
+        self.set((2 * r1 + 1, 2 * c1 + 1), PATH);
+        self.set((2 * r2 + 1, 2 * c2 + 1), PATH);
+        self.set((r1 + r2 + 1, c1 + c2 + 1), PATH);
+    }
+
+    // Up, right, down then left
+    fn neighbors(&self, (line, col): Cell) -> Vec<Cell> {
+        let mut cells = Vec::with_capacity(4);
+
+        if line > 0 {
+            cells.push((line - 1, col));
+        }
+        if col + 1 < self.width {
+            cells.push((line, col + 1));
+        }
+        if line + 1 < self.height {
+            cells.push((line + 1, col));
+        }
+        if col > 0 {
+            cells.push((line, col - 1));
+        }
+
+        cells
+    }
+
+    fn random_cell(&self, rng: &mut Rng) -> Cell {
+        (rng.below(self.height), rng.below(self.width))
+    }
+
+    fn into_maze(mut self) -> Result<Maze, MazeError> {
+        let (width, height) = (2 * self.width + 1, 2 * self.height + 1);
+
+        // Entrance and exit
+        self.set((0, 1), PATH);
+        self.set((height - 1, width - 2), PATH);
+
+        Maze::new(width, height, self.squares)
+    }
+}
+
+// ---------------------
+// Recursive Backtracker
+// ---------------------
+fn recursive_backtracker(grid: &mut Grid, rng: &mut Rng) {
+    let mut visited = vec![false; grid.len()];
+    let start = grid.random_cell(rng);
+
+    let mut stack = vec![start];
+    visited[grid.index(start)] = true;
+    grid.connect(start, start);
+
+    while let Some(&cell) = stack.last() {
+        let unvisited: Vec<Cell> = grid
+            .neighbors(cell)
+            .into_iter()
+            .filter(|&next| !visited[grid.index(next)])
+            .collect();
+
+        if unvisited.is_empty() {
+            stack.pop();
+            continue;
+        }
+
+        let next = rng.pick(&unvisited);
+        grid.connect(cell, next);
+        visited[grid.index(next)] = true;
+        stack.push(next);
+    }
+}
+
+// ----
+// Prim
+// ----
+fn prim(grid: &mut Grid, rng: &mut Rng) {
+    let mut in_maze = vec![false; grid.len()];
+    let mut in_frontier = vec![false; grid.len()];
+    let mut frontier = Vec::new();
+
+    let start = grid.random_cell(rng);
+    in_maze[grid.index(start)] = true;
+    grid.connect(start, start);
+
+    for next in grid.neighbors(start) {
+        in_frontier[grid.index(next)] = true;
+        frontier.push(next);
+    }
+
+    while !frontier.is_empty() {
+        let cell = frontier.swap_remove(rng.below(frontier.len()));
+
+        let (inside, outside): (Vec<Cell>, Vec<Cell>) = grid
+            .neighbors(cell)
+            .into_iter()
+            .partition(|&next| in_maze[grid.index(next)]);
+
+        grid.connect(cell, rng.pick(&inside));
+        in_maze[grid.index(cell)] = true;
+
+        for next in outside {
+            if !in_frontier[grid.index(next)] {
+                in_frontier[grid.index(next)] = true;
+                frontier.push(next);
+            }
+        }
+    }
+}
+
+// -------
+// Kruskal
+// -------
+fn kruskal(grid: &mut Grid, rng: &mut Rng) {
+    let mut sets = DisjointSet::new(grid.len());
+    let mut edges = Vec::with_capacity(2 * grid.len());
+
+    for line in 0..grid.height {
+        for col in 0..grid.width {
+            if col + 1 < grid.width {
+                edges.push(((line, col), (line, col + 1)));
+            }
+            if line + 1 < grid.height {
+                edges.push(((line, col), (line + 1, col)));
+            }
+        }
+    }
+
+    rng.shuffle(&mut edges);
+
+    // A single cell has no edges, but it still has to be opened
+    grid.connect((0, 0), (0, 0));
+
+    for (a, b) in edges {
+        if sets.union(grid.index(a), grid.index(b)) {
+            grid.connect(a, b);
+        }
+    }
+}
+
+struct DisjointSet {
+    parents: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+
+        i
+    }
+
+    // False if they were already in the same set
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parents[a] = b;
+
+        a != b
+    }
+}
+
+// ------
+// Wilson
+// ------
+fn wilson(grid: &mut Grid, rng: &mut Rng) {
+    let mut in_maze = vec![false; grid.len()];
+    // Where the walk left each cell, the last exit wins so loops are erased
+    let mut exits: Vec<Option<Cell>> = vec![None; grid.len()];
+
+    let first = grid.random_cell(rng);
+    in_maze[grid.index(first)] = true;
+    grid.connect(first, first);
+
+    for line in 0..grid.height {
+        for col in 0..grid.width {
+            if in_maze[grid.index((line, col))] {
+                continue;
+            }
+
+            // Walk until the maze is found
+            let mut cell = (line, col);
+            while !in_maze[grid.index(cell)] {
+                let next = rng.pick(&grid.neighbors(cell));
+                exits[grid.index(cell)] = Some(next);
+                cell = next;
+            }
+
+            // Carve the walk, without its loops
+            let mut cell = (line, col);
+            while !in_maze[grid.index(cell)] {
+                let next = exits[grid.index(cell)].unwrap();
+                grid.connect(cell, next);
+                in_maze[grid.index(cell)] = true;
+                cell = next;
+            }
+        }
+    }
+}
+
+// -------------
+// Aldous-Broder
+// -------------
+fn aldous_broder(grid: &mut Grid, rng: &mut Rng) {
+    let mut visited = vec![false; grid.len()];
+
+    let mut cell = grid.random_cell(rng);
+    let mut remaining = grid.len() - 1;
+    visited[grid.index(cell)] = true;
+    grid.connect(cell, cell);
+
+    while remaining > 0 {
+        let next = rng.pick(&grid.neighbors(cell));
+
+        if !visited[grid.index(next)] {
+            grid.connect(cell, next);
+            visited[grid.index(next)] = true;
+            remaining -= 1;
+        }
+
+        cell = next;
+    }
+}
+
+// -----
+// Eller
+// -----
+fn eller(grid: &mut Grid, rng: &mut Rng) {
+    // The set of each cell of the current line, 0 is no set yet
+    let mut sets = vec![0; grid.width];
+    let mut next_set = 1;
+
+    for line in 0..grid.height {
+        let last_line = line + 1 == grid.height;
+
+        for set in sets.iter_mut().filter(|set| **set == 0) {
+            *set = next_set;
+            next_set += 1;
+        }
+
+        // Join some of the cells next to each other, all of them on the last line
+        for col in 0..grid.width - 1 {
+            if sets[col] != sets[col + 1] && (last_line || rng.coin()) {
+                grid.connect((line, col), (line, col + 1));
+
+                let (old, new) = (sets[col + 1], sets[col]);
+                for set in sets.iter_mut().filter(|set| **set == old) {
+                    *set = new;
+                }
+            } else {
+                grid.connect((line, col), (line, col));
+            }
+        }
+        grid.connect((line, grid.width - 1), (line, grid.width - 1));
+
+        if last_line {
+            break;
+        }
+
+        // Every set goes down at least once
+        let mut below = vec![0; grid.width];
+        let mut columns: Vec<usize> = (0..grid.width).collect();
+        rng.shuffle(&mut columns);
+
+        let mut done: Vec<usize> = Vec::new();
+        for &col in &columns {
+            let set = sets[col];
+
+            if !done.contains(&set) || rng.coin() {
+                grid.connect((line, col), (line + 1, col));
+                below[col] = set;
+                if !done.contains(&set) {
+                    done.push(set);
+                }
+            }
+        }
+
+        sets = below;
+    }
+}
+
+// -------------
+// Hunt and Kill
+// -------------
+fn hunt_and_kill(grid: &mut Grid, rng: &mut Rng) {
+    let mut visited = vec![false; grid.len()];
+
+    let mut cell = grid.random_cell(rng);
+    visited[grid.index(cell)] = true;
+    grid.connect(cell, cell);
+
+    loop {
+        // Kill: walk to unvisited cells until stuck
+        let unvisited: Vec<Cell> = grid
+            .neighbors(cell)
+            .into_iter()
+            .filter(|&next| !visited[grid.index(next)])
+            .collect();
+
+        if !unvisited.is_empty() {
+            let next = rng.pick(&unvisited);
+            grid.connect(cell, next);
+            visited[grid.index(next)] = true;
+            cell = next;
+            continue;
+        }
+
+        // Hunt: the first unvisited cell next to the maze
+        let hunted = (0..grid.height)
+            .flat_map(|line| (0..grid.width).map(move |col| (line, col)))
+            .filter(|&cell| !visited[grid.index(cell)])
+            .find_map(|cell| {
+                let inside: Vec<Cell> = grid
+                    .neighbors(cell)
+                    .into_iter()
+                    .filter(|&next| visited[grid.index(next)])
+                    .collect();
+
+                if inside.is_empty() {
+                    None
+                } else {
+                    Some((cell, inside))
+                }
+            });
+
+        match hunted {
+            Some((next, inside)) => {
+                grid.connect(next, rng.pick(&inside));
+                visited[grid.index(next)] = true;
+                cell = next;
+            }
+            None => break,
+        }
+    }
+}
+
+// ----------
+// Sidewinder
+// ----------
+fn sidewinder(grid: &mut Grid, rng: &mut Rng) {
+    for line in 0..grid.height {
+        let mut run_start = 0;
+
+        for col in 0..grid.width {
+            let last_col = col + 1 == grid.width;
+
+            // The first line is a single corridor
+            if line == 0 {
+                let next = if last_col { col } else { col + 1 };
+                grid.connect((0, col), (0, next));
+                continue;
+            }
+
+            if last_col || rng.coin() {
+                // Close the run, one of its cells goes up
+                let up = run_start + rng.below(col - run_start + 1);
+                grid.connect((line, up), (line - 1, up));
+                grid.connect((line, col), (line, col));
+                run_start = col + 1;
+            } else {
+                grid.connect((line, col), (line, col + 1));
+            }
+        }
+    }
+}
+
+// -----------
+// Binary Tree
+// -----------
+fn binary_tree(grid: &mut Grid, rng: &mut Rng) {
+    for line in 0..grid.height {
+        for col in 0..grid.width {
+            // Up or left
+            let mut choices = Vec::with_capacity(2);
+
+            if line > 0 {
+                choices.push((line - 1, col));
+            }
+            if col > 0 {
+                choices.push((line, col - 1));
+            }
+
+            let next = if choices.is_empty() {
+                (line, col)
+            } else {
+                rng.pick(&choices)
+            };
+
+            grid.connect((line, col), next);
+        }
+    }
+}
+
+// ------------------
+// Recursive Division
+// ------------------
+fn recursive_division(grid: &mut Grid, rng: &mut Rng) {
+    // Start from an empty room, walls are added afterwards
+    let (width, height) = (2 * grid.width + 1, 2 * grid.height + 1);
+    for line in 1..height - 1 {
+        for col in 1..width - 1 {
+            grid.set((line, col), PATH);
+        }
+    }
+
+    // Chambers to divide, as (line, column, height, width) in cells.
+    //  A stack instead of recursion, big mazes would overflow
+    let mut chambers = vec![(0, 0, grid.height, grid.width)];
+
+    while let Some((line, col, h, w)) = chambers.pop() {
+        if h < 2 && w < 2 {
+            continue;
+        }
+
+        let horizontal = if w < 2 {
+            true
+        } else if h < 2 {
+            false
+        } else if h != w {
+            h > w
+        } else {
+            rng.coin()
+        };
+
+        if horizontal {
+            // Wall between cell lines `line + k - 1` and `line + k`
+            let k = 1 + rng.below(h - 1);
+            let gap = col + rng.below(w);
+
+            for c in col..col + w {
+                if c != gap {
+                    grid.set((2 * (line + k), 2 * c + 1), WALL);
+                }
+                grid.set((2 * (line + k), 2 * c + 2), WALL);
+            }
+
+            chambers.push((line, col, k, w));
+            chambers.push((line + k, col, h - k, w));
+        } else {
+            let k = 1 + rng.below(w - 1);
+            let gap = line + rng.below(h);
+
+            for l in line..line + h {
+                if l != gap {
+                    grid.set((2 * l + 1, 2 * (col + k)), WALL);
+                }
+                grid.set((2 * l + 2, 2 * (col + k)), WALL);
+            }
+
+            chambers.push((line, col, h, k));
+            chambers.push((line, col + k, h, w - k));
+        }
+    }
+}
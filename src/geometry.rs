@@ -0,0 +1,223 @@
+use image::DynamicImage;
+use image::GenericImage;
+use image::Rgba;
+
+use crate::constants::WALL;
+use crate::{img_to_vec, Maze, MazeError, Pos, SOLVED_COLOR};
+
+use std::convert::TryFrom;
+
+// How big the walls and the corridors of a maze image are, in pixels.
+//  The image starts with a wall on the top and on the left, then corridors
+//  and walls alternate: a wall square is `wall` pixels wide, a corridor
+//  square is `corridor` pixels wide
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellGeometry {
+    pub wall: u32,
+    pub corridor: u32,
+}
+
+impl CellGeometry {
+    // One pixel per square, what img_to_maze does
+    pub const PIXEL: CellGeometry = CellGeometry {
+        wall: 1,
+        corridor: 1,
+    };
+
+    // square and pixels divide by the pitch, so both have to be
+    //  at least a pixel thick
+    pub fn check(&self) -> Result<(), MazeError> {
+        if self.wall == 0 || self.corridor == 0 {
+            return Err(MazeError::ZeroThickness);
+        }
+
+        Ok(())
+    }
+
+    // A wall and a corridor
+    pub fn pitch(&self) -> u32 {
+        self.wall + self.corridor
+    }
+
+    // Which square a pixel is in, along one axis
+    pub fn square(&self, pixel: u32) -> usize {
+        let band = 2 * (pixel / self.pitch()) as usize;
+
+        if pixel % self.pitch() < self.wall {
+            band
+        } else {
+            band + 1
+        }
+    }
+
+    // The pixels a square covers, along one axis
+    pub fn pixels(&self, square: usize) -> (u32, u32) {
+        let start = (square / 2) as u32 * self.pitch();
+
+        if square % 2 == 0 {
+            (start, start + self.wall)
+        } else {
+            (start + self.wall, start + self.pitch())
+        }
+    }
+}
+
+// The thinnest wall and the thinnest corridor found on the lines
+//  and the columns of the image
+pub fn detect_cell_geometry(img: &DynamicImage) -> Result<CellGeometry, MazeError> {
+    let pixels = img_to_vec(img)?;
+    let width = pixels[0].len();
+
+    let mut wall = usize::MAX;
+    let mut corridor = usize::MAX;
+
+    let mut check_run = |square: u8, length: usize| {
+        if square == WALL {
+            wall = wall.min(length);
+        } else {
+            corridor = corridor.min(length);
+        }
+    };
+
+    for line in &pixels {
+        for (square, length) in runs(line.iter().copied()) {
+            check_run(square, length);
+        }
+    }
+
+    for col in 0..width {
+        for (square, length) in runs(pixels.iter().map(|line| line[col])) {
+            check_run(square, length);
+        }
+    }
+
+    // No wall or no corridor at all, nothing to scale
+    if wall == usize::MAX || corridor == usize::MAX {
+        return Ok(CellGeometry::PIXEL);
+    }
+
+    Ok(CellGeometry {
+        wall: u32::try_from(wall).unwrap(),
+        corridor: u32::try_from(corridor).unwrap(),
+    })
+}
+
+// Lengths of the runs of the same square
+fn runs(squares: impl Iterator<Item = u8>) -> Vec<(u8, usize)> {
+    let mut runs: Vec<(u8, usize)> = Vec::new();
+
+    for square in squares {
+        match runs.last_mut() {
+            Some((last, length)) if *last == square => *length += 1,
+            _ => runs.push((square, 1)),
+        }
+    }
+
+    runs
+}
+
+// One square per wall or corridor instead of one per pixel. The geometry is
+//  detected when it isn't given, and returned to draw the solution back
+pub fn img_to_maze_scaled(
+    img: &DynamicImage,
+    geometry: Option<CellGeometry>,
+) -> Result<(Maze, CellGeometry), MazeError> {
+    let geometry = match geometry {
+        Some(geometry) => geometry,
+        None => detect_cell_geometry(img)?,
+    };
+
+    geometry.check()?;
+
+    let pixels = img_to_vec(img)?;
+
+    let width = geometry.square(img.width() - 1) + 1;
+    let height = geometry.square(img.height() - 1) + 1;
+
+    // The middle pixel of each square decides what it is
+    let middle = |square: usize, size: u32| {
+        let (start, end) = geometry.pixels(square);
+        ((start + end) / 2).min(size - 1) as usize
+    };
+
+    let mut cells = Vec::with_capacity(width * height);
+
+    for line in 0..height {
+        let y = middle(line, img.height());
+
+        for col in 0..width {
+            cells.push(pixels[y][middle(col, img.width())]);
+        }
+    }
+
+    Ok((Maze::new(width, height, cells)?, geometry))
+}
+
+// Black walls and white paths, each square as big as the geometry says
+pub fn maze_to_img_scaled(maze: &Maze, geometry: CellGeometry) -> Result<DynamicImage, MazeError> {
+    geometry.check()?;
+
+    // Where the last square ends
+    let (_, width) = geometry.pixels(maze.width() - 1);
+    let (_, height) = geometry.pixels(maze.height() - 1);
+
+    let mut img = DynamicImage::new_rgb8(width, height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let square = (geometry.square(y), geometry.square(x));
+            let color = if maze[square] == WALL {
+                Rgba([0, 0, 0, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            };
+
+            img.put_pixel(x, y, color);
+        }
+    }
+
+    Ok(img)
+}
+
+// Paints every square of the solution over all of its pixels,
+//  so the solution is as wide as the corridors
+pub fn output_maze_solution_scaled(
+    img: &DynamicImage,
+    solution: &[Pos],
+    geometry: CellGeometry,
+    output: &str,
+) -> Result<(), MazeError> {
+    render_solution_scaled(img, solution, geometry)?
+        .save(output)
+        .map_err(MazeError::ImageEncode)
+}
+
+// The image with the solution painted over it, see output_maze_solution_scaled
+pub fn render_solution_scaled(
+    img: &DynamicImage,
+    solution: &[Pos],
+    geometry: CellGeometry,
+) -> Result<DynamicImage, MazeError> {
+    geometry.check()?;
+
+    let mut new_img = img.clone();
+
+    for &(line, col) in solution {
+        let (top, bottom) = geometry.pixels(line);
+        let (left, right) = geometry.pixels(col);
+
+        // A square outside of the image, like output_maze_solution. The last
+        //  line and column can be cut short by the edge of the image
+        if top >= img.height() || left >= img.width() {
+            return Err(MazeError::BlockedSquare((line, col)));
+        }
+
+        for y in top..bottom.min(img.height()) {
+            for x in left..right.min(img.width()) {
+                new_img.put_pixel(x, y, SOLVED_COLOR);
+            }
+        }
+    }
+
+    Ok(new_img)
+}
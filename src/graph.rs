@@ -0,0 +1,248 @@
+use crate::{Maze, MazeError, Pos, SolveResult};
+
+// No node on this square
+const NO_NODE: usize = usize::MAX;
+
+// A compressed sparse row graph: the edges of node `n` are
+//  `edges[offsets[n]..offsets[n + 1]]`, nodes are numbered line by line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graph {
+    offsets: Vec<usize>,
+    edges: Vec<usize>,
+    // What it costs to go through each edge
+    weights: Vec<u64>,
+    // Node id -> square
+    positions: Vec<Pos>,
+    // Square index (see Maze::index) -> node id
+    ids: Vec<usize>,
+    width: usize,
+}
+
+impl Graph {
+    // Every open square is a node, edges go to the open squares next to it
+    //  (down, right, up then left) and cost as much as the square they go to
+    pub fn from_maze(maze: &Maze) -> Self {
+        Self::build(
+            maze,
+            |_| true,
+            |_, next| Some((next, u64::from(maze.cost(next)))),
+        )
+    }
+
+    // Only dead ends, junctions, the start and the end are nodes. Edges
+    //  follow the corridors between them and cost as much as the corridor
+    pub fn compressed(maze: &Maze) -> Self {
+        Self::build(
+            maze,
+            |square| is_junction(maze, square),
+            |from, next| follow_corridor(maze, from, next, |_| ()),
+        )
+    }
+
+    // `follow` gives where an edge leaving `square` through `next` ends
+    //  and its cost
+    fn build<N, F>(maze: &Maze, is_node: N, mut follow: F) -> Self
+    where
+        N: Fn(Pos) -> bool,
+        F: FnMut(Pos, Pos) -> Option<(Pos, u64)>,
+    {
+        let mut ids = vec![NO_NODE; maze.len()];
+        let mut positions = Vec::new();
+
+        for square in maze.open_squares().filter(|&square| is_node(square)) {
+            ids[maze.index(square)] = positions.len();
+            positions.push(square);
+        }
+
+        let mut offsets = Vec::with_capacity(positions.len() + 1);
+        let mut edges = Vec::with_capacity(positions.len() * 2);
+        let mut weights = Vec::with_capacity(positions.len() * 2);
+
+        offsets.push(0);
+
+        for &square in &positions {
+            for next in maze.neighbors(square) {
+                if let Some((end, cost)) = follow(square, next) {
+                    edges.push(ids[maze.index(end)]);
+                    weights.push(cost);
+                }
+            }
+
+            offsets.push(edges.len());
+        }
+
+        Self {
+            offsets,
+            edges,
+            weights,
+            positions,
+            ids,
+            width: maze.width(),
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    // Each corridor is counted once per direction
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    // The node on that square, if there's one
+    pub fn id(&self, (line, col): Pos) -> Option<usize> {
+        match self.ids.get(line * self.width + col) {
+            Some(&id) if id != NO_NODE && col < self.width => Some(id),
+            _ => None,
+        }
+    }
+
+    pub fn pos(&self, id: usize) -> Pos {
+        self.positions[id]
+    }
+
+    pub fn neighbors(&self, id: usize) -> &[usize] {
+        &self.edges[self.offsets[id]..self.offsets[id + 1]]
+    }
+
+    // Same order as neighbors
+    pub fn weights(&self, id: usize) -> &[u64] {
+        &self.weights[self.offsets[id]..self.offsets[id + 1]]
+    }
+}
+
+// Dead ends, junctions, the start and the end,
+//  everything that isn't in the middle of a corridor
+fn is_junction(maze: &Maze, square: Pos) -> bool {
+    maze.neighbors(square).count() != 2
+        || maze.start().ok() == Some(square)
+        || maze.end().ok() == Some(square)
+}
+
+// Walks from the node `from` through `next` until the next node, `corridor`
+//  is given every square in between. None if the corridor leads nowhere
+fn follow_corridor<F>(maze: &Maze, from: Pos, next: Pos, mut corridor: F) -> Option<(Pos, u64)>
+where
+    F: FnMut(Pos),
+{
+    let (mut previous, mut current) = (from, next);
+    let mut cost = u64::from(maze.cost(current));
+
+    // A corridor square has exactly two ways out
+    while !is_junction(maze, current) {
+        corridor(current);
+
+        let following = maze.neighbors(current).find(|&square| square != previous)?;
+
+        previous = current;
+        current = following;
+        cost += u64::from(maze.cost(current));
+    }
+
+    Some((current, cost))
+}
+
+// -------------
+// JunctionGraph
+// -------------
+// The compressed graph along with the squares of every corridor, so a path
+//  found on the graph can be turned back into a path on the maze
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JunctionGraph {
+    graph: Graph,
+    // The squares between the two nodes of edge `e` are
+    //  `corridors[corridor_offsets[e]..corridor_offsets[e + 1]]`
+    corridor_offsets: Vec<usize>,
+    corridors: Vec<Pos>,
+}
+
+impl JunctionGraph {
+    pub fn new(maze: &Maze) -> Self {
+        let mut corridor_offsets = vec![0];
+        let mut corridors = Vec::new();
+
+        // Same as Graph::compressed, the squares are kept while
+        //  the corridors are walked, one edge at a time
+        let graph = Graph::build(
+            maze,
+            |square| is_junction(maze, square),
+            |from, next| {
+                let len = corridors.len();
+                let end = follow_corridor(maze, from, next, |pos| corridors.push(pos));
+
+                match end {
+                    Some(_) => corridor_offsets.push(corridors.len()),
+                    None => corridors.truncate(len),
+                }
+
+                end
+            },
+        );
+
+        Self {
+            graph,
+            corridor_offsets,
+            corridors,
+        }
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    // The squares between `from` and `to`, the cheapest corridor
+    //  if there's more than one. None if they aren't linked
+    pub fn corridor(&self, from: usize, to: usize) -> Option<&[Pos]> {
+        let first = self.graph.offsets[from];
+
+        let (edge, _) = self
+            .graph
+            .neighbors(from)
+            .iter()
+            .zip(self.graph.weights(from))
+            .enumerate()
+            .filter(|&(_, (&next, _))| next == to)
+            .min_by_key(|&(_, (_, &weight))| weight)?;
+
+        let edge = first + edge;
+
+        Some(&self.corridors[self.corridor_offsets[edge]..self.corridor_offsets[edge + 1]])
+    }
+
+    // Puts the corridors back between the nodes of a path found on the graph.
+    //  Squares that aren't nodes, or nodes that aren't linked, are kept as is
+    pub fn expand(&self, path: &[Pos]) -> Vec<Pos> {
+        let mut expanded = Vec::with_capacity(path.len());
+
+        for (i, &square) in path.iter().enumerate() {
+            if i > 0 {
+                let ids = (self.graph.id(path[i - 1]), self.graph.id(square));
+
+                if let (Some(from), Some(to)) = ids {
+                    expanded.extend_from_slice(self.corridor(from, to).unwrap_or(&[]));
+                }
+            }
+
+            expanded.push(square);
+        }
+
+        expanded
+    }
+
+    // Runs a graph solver (see algorithms::depth_first and the others) on
+    //  the graph, the path it finds is expanded back to every square
+    pub fn solve<F>(&self, maze: &Maze, search: F) -> Result<SolveResult, MazeError>
+    where
+        F: FnOnce(&Maze, &Graph) -> Result<SolveResult, MazeError>,
+    {
+        let mut result = search(maze, &self.graph)?;
+
+        // Two nodes can be linked by more than one corridor,
+        //  the cheapest one is taken so the cost can change
+        result.path = result.path.map(|path| self.expand(&path));
+        result.cost = result.path.as_ref().map(|path| maze.path_cost(path));
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,94 @@
+use image::DynamicImage;
+
+use crate::geometry::img_to_maze_scaled;
+use crate::preprocess::{photo_to_maze, PreprocessConfig};
+use crate::text::{self, Charset};
+use crate::threshold::{img_to_maze_with_config, AppliedThreshold, ThresholdConfig};
+use crate::{detect_markers, get_image, CellGeometry, MarkerColors, Maze, MazeError, Pos};
+
+use std::path::Path;
+
+// How a maze file is read, text mazes only look at `start` and `end`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InputConfig {
+    // For images that are neither thick nor photos
+    pub threshold: ThresholdConfig,
+    // Cleans up a photo or a scan first, with its own threshold
+    pub photo: Option<PreprocessConfig>,
+    // The walls and the corridors are more than a pixel wide
+    pub thick: bool,
+    // Not looked for on photos, they are straightened and cropped
+    pub markers: Option<MarkerColors>,
+    pub start: Option<Pos>,
+    pub end: Option<Pos>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoadedMaze {
+    pub maze: Maze,
+    // None for text mazes
+    pub image: Option<DynamicImage>,
+    // Which square each pixel of the image is in, None when the squares
+    //  can't be traced back to the pixels (photos)
+    pub geometry: Option<CellGeometry>,
+    // The threshold the image was read with, when it isn't a photo or thick
+    pub applied: Option<AppliedThreshold>,
+}
+
+// A text maze if it ends with .txt, otherwise an image
+pub fn load_maze(path: &Path, config: &InputConfig) -> Result<LoadedMaze, MazeError> {
+    let is_text = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("txt"));
+
+    let mut loaded = if is_text {
+        LoadedMaze {
+            maze: text::load_text(&path.to_string_lossy(), &Charset::default())?,
+            image: None,
+            geometry: None,
+            applied: None,
+        }
+    } else {
+        load_image(get_image(path.to_string_lossy().into_owned())?, config)?
+    };
+
+    if let Some(start) = config.start {
+        loaded.maze = loaded.maze.with_start(start)?;
+    }
+    if let Some(end) = config.end {
+        loaded.maze = loaded.maze.with_end(end)?;
+    }
+
+    Ok(loaded)
+}
+
+fn load_image(img: DynamicImage, config: &InputConfig) -> Result<LoadedMaze, MazeError> {
+    let (mut maze, geometry, applied) = if let Some(photo) = &config.photo {
+        (photo_to_maze(&img, photo)?, None, None)
+    } else if config.thick {
+        let (maze, geometry) = img_to_maze_scaled(&img, None)?;
+        (maze, Some(geometry), None)
+    } else {
+        let (maze, applied) = img_to_maze_with_config(&img, &config.threshold)?;
+        (maze, Some(CellGeometry::PIXEL), Some(applied))
+    };
+
+    if let (Some(colors), Some(geometry)) = (&config.markers, geometry) {
+        let (start, end) = detect_markers(&img, colors);
+        let square = |(y, x): Pos| (geometry.square(y as u32), geometry.square(x as u32));
+
+        if let Some(start) = start {
+            maze = maze.with_start(square(start))?;
+        }
+        if let Some(end) = end {
+            maze = maze.with_end(square(end))?;
+        }
+    }
+
+    Ok(LoadedMaze {
+        maze,
+        image: Some(img),
+        geometry,
+        applied,
+    })
+}
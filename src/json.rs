@@ -0,0 +1,49 @@
+// Just enough JSON to write reports, without pulling a serializer in
+
+// A JSON string, quoted and escaped
+pub(crate) fn string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+
+    json.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+
+    json
+}
+
+// `null` when there's no value
+pub(crate) fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| String::from("null"), |value| value.to_string())
+}
+
+// { "key": value, ... } on one line, the values are already JSON
+pub(crate) fn object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| format!("{}: {}", string(key), value))
+        .collect();
+
+    format!("{{{}}}", fields.join(", "))
+}
+
+// The same with one field per line, for a report on its own
+pub(crate) fn pretty_object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| format!("  {}: {}", string(key), value))
+        .collect();
+
+    format!("{{\n{}\n}}\n", fields.join(",\n"))
+}
@@ -1,52 +1,83 @@
 use image::DynamicImage;
 use image::GenericImage;
 use image::GenericImageView;
+use image::Rgb;
+use image::RgbImage;
 use image::Rgba;
 
-mod constants;
-use constants::{PATH, WALL};
+pub mod constants;
+pub use constants::{PATH, WALL};
 
 pub mod algorithms;
+pub mod analysis;
+pub mod animation;
+pub mod batch;
 pub mod data_structures;
+pub mod error;
+pub mod generators;
+pub mod geometry;
+pub mod graph;
+pub mod input;
+mod json;
+pub mod maze;
+pub mod preprocess;
+pub mod render;
+pub mod solver;
+pub mod svg;
+pub mod terminal;
+pub mod text;
+pub mod threshold;
+pub mod validate;
+
+pub use error::MazeError;
+pub use geometry::CellGeometry;
+pub use graph::{Graph, JunctionGraph};
+pub use maze::Maze;
+pub use render::{output_maze_solution_with_style, OutputFormat, RenderStyle, SolutionColor};
+pub use solver::{Registry, SolveResult, SolveStats, Solver};
+pub use threshold::{img_to_maze_with_config, img_to_vec_with_config};
+pub use threshold::{AppliedThreshold, ThresholdConfig, ThresholdMethod};
+pub use validate::{validate_path, PathReport, Violation};
 
 use std::collections::HashMap;
 
 use std::convert::TryFrom;
 use std::env::Args;
 
-// When checking the image pixel's alpha,
-//  if it's more than this value => WALL
-const WALL_LIMIT: u8 = 50;
+// A pixel is a WALL if its red, green and blue are all below this value,
+//  see ThresholdConfig for other ways
+pub const WALL_LIMIT: u8 = 50;
 
 // The color that is written to the image when
-//  a PATH is selected by one of the algorithms
-const SOLVED_COLOR: Rgba<u8> = Rgba([255, 0, 0, 0]);
+//  a PATH is selected by one of the algorithms, see RenderStyle for others
+pub(crate) const SOLVED_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
 
-pub fn parse_image(args: &mut Args) -> DynamicImage {
+// A square of the maze, as (line, column)
+pub type Pos = (usize, usize);
+
+// Every open square mapped to the open squares next to it
+pub type AdjacencyList = HashMap<Pos, Vec<Pos>>;
+
+pub fn parse_image(args: &mut Args) -> Result<DynamicImage, MazeError> {
     // Get the arguments (image name)
     let args = args.nth(1);
 
-    let filename = match args {
-        Some(filename) => filename,
-        None => {
-            eprintln!("Image not found! Exiting..");
-            std::process::exit(1);
-        }
-    };
-
-    get_image(filename)
+    match args {
+        Some(filename) => get_image(filename),
+        None => Err(MazeError::MissingArgument),
+    }
 }
 
-pub fn get_image(filename: String) -> DynamicImage {
-    match image::open(filename) {
-        Ok(image) => image,
-        Err(_) => {
-            eprintln!("Image not found! Exiting..");
-            std::process::exit(1);
-        }
-    }
+pub fn get_image(filename: String) -> Result<DynamicImage, MazeError> {
+    image::open(filename).map_err(MazeError::ImageDecode)
 }
 
+// What print_img and print_maze_solution print for each square
+const WALL_CHAR: char = 'X';
+const PATH_CHAR: char = '-';
+const SOLUTION_CHAR: char = '*';
+
+// Plain characters, see the terminal module for colors
 pub fn print_img(img: &DynamicImage) {
     for pixel in img.pixels() {
         if pixel.0 == 0 {
@@ -57,153 +88,315 @@ pub fn print_img(img: &DynamicImage) {
         // If the RGB(r, g, b) values are bellow a certain level,
         //  it's a wall (darker pixel)
         if pixel.2[0] < WALL_LIMIT && pixel.2[1] < WALL_LIMIT && pixel.2[2] < WALL_LIMIT {
-            print!("X");
+            print!("{}", WALL_CHAR);
             continue;
         }
 
-        print!("-"); // Path
+        print!("{}", PATH_CHAR);
     }
     println!();
 }
 
-pub fn img_to_vec(img: &DynamicImage) -> Vec<Vec<u8>> {
-    // Will panic on a 16-bit platform
-    let (width, height): (usize, usize) = {
-        (
-            usize::try_from(img.width()).unwrap(),
-            usize::try_from(img.height()).unwrap(),
-        )
+pub fn img_to_vec(img: &DynamicImage) -> Result<Vec<Vec<u8>>, MazeError> {
+    img_to_vec_with_threshold(img, WALL_LIMIT)
+}
+
+// A pixel is a wall if its red, green and blue are all below `threshold`
+pub fn img_to_vec_with_threshold(
+    img: &DynamicImage,
+    threshold: u8,
+) -> Result<Vec<Vec<u8>>, MazeError> {
+    let config = ThresholdConfig {
+        method: ThresholdMethod::Channels {
+            red: threshold,
+            green: threshold,
+            blue: threshold,
+        },
+        transparent_walls: None,
     };
-    let mut matrix = vec![vec![0; width]; height];
 
-    let mut line = 0;
-    let mut col = 0;
+    Ok(img_to_vec_with_config(img, &config)?.0)
+}
 
-    // Due to the format of the img,
-    //  we can't really iterate it like we usually do
+pub fn img_to_maze(img: &DynamicImage) -> Result<Maze, MazeError> {
+    Maze::from_rows(&img_to_vec(img)?)
+}
 
-    for pixel in img.pixels() {
-        // pixel.0 -> column
-        // pixel.1 -> line
+pub fn img_to_maze_with_threshold(img: &DynamicImage, threshold: u8) -> Result<Maze, MazeError> {
+    Maze::from_rows(&img_to_vec_with_threshold(img, threshold)?)
+}
 
-        if pixel.0 == 0 {
-            // If the column is zero, then we reached a new line
-            if pixel.1 != 0 {
-                // Ignore the first line
-                col = 0;
-                line += 1;
-            }
+// Pixels of these colors mark the start and the end of the maze
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkerColors {
+    pub start: Rgb<u8>,
+    pub end: Rgb<u8>,
+    // How far off each channel can be and still count as the marker
+    pub tolerance: u8,
+}
+
+impl Default for MarkerColors {
+    // Green start, blue end
+    fn default() -> Self {
+        Self {
+            start: Rgb([0, 255, 0]),
+            end: Rgb([0, 0, 255]),
+            tolerance: 30,
         }
+    }
+}
 
-        // If the RGB(r, g, b) values are bellow a certain level,
-        //  it's a wall (darker pixel)
-        if pixel.2[0] < WALL_LIMIT && pixel.2[1] < WALL_LIMIT && pixel.2[2] < WALL_LIMIT {
-            matrix[line][col] = WALL;
+// The first pixel (line by line) of each marker color
+pub fn detect_markers(img: &DynamicImage, colors: &MarkerColors) -> (Option<Pos>, Option<Pos>) {
+    let matches = |Rgba([r, g, b, _]): Rgba<u8>, Rgb(marker): Rgb<u8>| {
+        [r, g, b]
+            .iter()
+            .zip(marker.iter())
+            .all(|(&channel, &wanted)| channel.abs_diff(wanted) <= colors.tolerance)
+    };
 
-            col += 1;
-            continue;
+    let mut start = None;
+    let mut end = None;
+
+    for (x, y, pixel) in img.pixels() {
+        let pos = (y as usize, x as usize);
+
+        if start.is_none() && matches(pixel, colors.start) {
+            start = Some(pos);
+        } else if end.is_none() && matches(pixel, colors.end) {
+            end = Some(pos);
         }
 
-        matrix[line][col] = PATH;
-        col += 1;
+        if start.is_some() && end.is_some() {
+            break;
+        }
+    }
+
+    (start, end)
+}
+
+// Same as img_to_maze, but the start and the end come from the markers.
+//  A missing marker falls back to looking at the borders
+pub fn img_to_maze_with_markers(
+    img: &DynamicImage,
+    colors: &MarkerColors,
+) -> Result<Maze, MazeError> {
+    let mut maze = img_to_maze(img)?;
+    let (start, end) = detect_markers(img, colors);
+
+    if let Some(start) = start {
+        maze = maze.with_start(start)?;
+    }
+    if let Some(end) = end {
+        maze = maze.with_end(end)?;
+    }
+
+    Ok(maze)
+}
+
+// Black walls and white paths, one pixel per square
+pub fn maze_to_img(maze: &Maze) -> DynamicImage {
+    let mut img = DynamicImage::new_rgb8(maze.width() as u32, maze.height() as u32);
+
+    for line in 0..maze.height() {
+        for col in 0..maze.width() {
+            let color = if maze[(line, col)] == WALL {
+                Rgba([0, 0, 0, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            };
+
+            img.put_pixel(col as u32, line as u32, color);
+        }
     }
 
-    matrix
+    img
+}
+
+// How the pixels of an image become the cost of a square
+//  (e.g. gray pixels for mud or slow terrain)
+#[derive(Debug, Clone)]
+pub enum WeightMode {
+    // White costs `min_cost`, the darkest pixels that aren't walls (luminance
+    //  of WALL_LIMIT or below) cost `max_cost`, and the grays in between
+    Luminance { min_cost: u32, max_cost: u32 },
+    // Each square costs as much as the closest color of the palette
+    Palette(Vec<(Rgb<u8>, u32)>),
+}
+
+// Walls are still found with WALL_LIMIT, the other pixels are given a cost
+pub fn img_to_weighted_maze(img: &DynamicImage, mode: &WeightMode) -> Result<Maze, MazeError> {
+    let maze = img_to_maze(img)?;
+
+    let costs = img
+        .pixels()
+        .map(|(_, _, pixel)| pixel_cost(pixel, mode))
+        .collect();
+
+    maze.with_costs(costs)
 }
 
-pub fn print_maze_solution(maze: &[Vec<u8>], solution: Vec<(usize, usize)>) {
-    for (i, row) in maze.iter().enumerate() {
-        for (j, &col) in row.iter().enumerate() {
-            if col == WALL {
-                print!("-"); // Wall
-                continue;
-            }
+fn pixel_cost(Rgba([r, g, b, _]): Rgba<u8>, mode: &WeightMode) -> u32 {
+    match mode {
+        WeightMode::Luminance { min_cost, max_cost } => {
+            // Anything darker is a wall, or close to one
+            let luma = u64::from(threshold::luma(r, g, b).max(WALL_LIMIT));
+            let range = u64::from(max_cost.saturating_sub(*min_cost));
+            let span = u64::from(255 - WALL_LIMIT);
 
-            if solution.contains(&(i, j)) {
-                print!("X"); // Path traversed during solving
-                continue;
-            }
+            *min_cost + (range * (255 - luma) / span) as u32
+        }
+        WeightMode::Palette(palette) => palette
+            .iter()
+            .min_by_key(|(Rgb([pr, pg, pb]), _)| {
+                let dr = i32::from(r) - i32::from(*pr);
+                let dg = i32::from(g) - i32::from(*pg);
+                let db = i32::from(b) - i32::from(*pb);
+
+                dr * dr + dg * dg + db * db
+            })
+            .map_or(1, |&(_, cost)| cost),
+    }
+}
 
-            print!("*"); // Path that wasn't traversed
+// Same characters as print_img, the solution is drawn over the paths
+pub fn print_maze_solution(maze: &Maze, solution: &[Pos]) {
+    let mut on_solution = vec![false; maze.len()];
+
+    for &square in solution.iter().filter(|&&square| maze.contains(square)) {
+        on_solution[maze.index(square)] = true;
+    }
+
+    for i in 0..maze.height() {
+        for j in 0..maze.width() {
+            let square = if maze[(i, j)] == WALL {
+                WALL_CHAR
+            } else if on_solution[maze.index((i, j))] {
+                SOLUTION_CHAR
+            } else {
+                PATH_CHAR
+            };
+
+            print!("{}", square);
         }
 
         println!(); // Newline
     }
 }
 
-pub fn output_maze_solution(img: &DynamicImage, solution: Vec<(usize, usize)>, output: &str) {
+pub fn output_maze_solution(
+    img: &DynamicImage,
+    solution: &[Pos],
+    output: &str,
+) -> Result<(), MazeError> {
     let mut new_img = img.clone();
 
-    for (x, y) in solution.iter() {
-        new_img.put_pixel(
-            u32::try_from(*y).unwrap(),
-            u32::try_from(*x).unwrap(),
-            SOLVED_COLOR,
-        );
-    }
+    for &(line, col) in solution.iter() {
+        // A square outside of the image, e.g. from a maze of another size
+        let (x, y) = match (u32::try_from(col), u32::try_from(line)) {
+            (Ok(x), Ok(y)) if new_img.in_bounds(x, y) => (x, y),
+            _ => return Err(MazeError::BlockedSquare((line, col))),
+        };
 
-    match new_img.save(output) {
-        Ok(_) => println!("Solution printed to: {}", output),
-        Err(e) => eprintln!("Error saving image!\n{}", e),
+        new_img.put_pixel(x, y, SOLVED_COLOR);
     }
+
+    new_img.save(output).map_err(MazeError::ImageEncode)
+}
+
+// -------
+// Heatmap
+// -------
+// How a distance map (see algorithms::distance_map) is colored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeatmapStyle {
+    // From the closest squares to the furthest, evenly spaced
+    pub gradient: Vec<Rgb<u8>>,
+    pub wall: Rgb<u8>,
+    // Open squares with no distance
+    pub unreachable: Rgb<u8>,
 }
 
-pub fn maze_to_adjacency_list(maze: &[Vec<u8>]) -> HashMap<(usize, usize), Vec<(usize, usize)>> {
-    let mut adj_list = HashMap::with_capacity(maze.len() * maze[0].len());
-
-    for (i, row) in maze.iter().enumerate() {
-        for (j, _col) in row.iter().enumerate() {
-            // No path from a WALL to anywhere else
-            if maze[i][j] == WALL {
-                continue;
-            }
-
-            // Check if there is a path in the 4 possible directions
-            // Check down
-            if i != (maze.len() - 1) && maze[i + 1][j] == PATH {
-                // We don't have to check if a key is in there because it's
-                //  the first time it's added
-                let mut vec = Vec::with_capacity(4);
-
-                vec.push((i + 1, j));
-                adj_list.insert((i, j), vec);
-            }
-
-            // Check right
-            if j != (maze[0].len() - 1) && maze[i][j + 1] == PATH {
-                add_node(&mut adj_list, (i, j), (i, j + 1));
-            }
-
-            // Check up
-            if i != 0 && maze[i - 1][j] == PATH {
-                add_node(&mut adj_list, (i, j), (i - 1, j));
-            }
-
-            // Check left
-            if j != 0 && maze[i][j - 1] == PATH {
-                add_node(&mut adj_list, (i, j), (i, j - 1));
-            }
+impl Default for HeatmapStyle {
+    fn default() -> Self {
+        Self {
+            gradient: vec![
+                Rgb([68, 1, 84]),
+                Rgb([59, 82, 139]),
+                Rgb([33, 145, 140]),
+                Rgb([94, 201, 98]),
+                Rgb([253, 231, 37]),
+            ],
+            wall: Rgb([0, 0, 0]),
+            unreachable: Rgb([128, 128, 128]),
         }
     }
-
-    adj_list
 }
 
-fn add_node(
-    adj_list: &mut HashMap<(usize, usize), Vec<(usize, usize)>>,
-    (x, y): (usize, usize),   // Current node
-    (dx, dy): (usize, usize), // Next node
-) {
-    match adj_list.get_mut(&(x, y)) {
-        Some(vec) => {
-            vec.push((dx, dy));
+// The color `t` of the way along the gradient, from 0 to 1
+pub fn gradient_color(gradient: &[Rgb<u8>], t: f64) -> Rgb<u8> {
+    match gradient {
+        [] => Rgb([255, 255, 255]),
+        [color] => *color,
+        _ => {
+            let at = t.clamp(0.0, 1.0) * (gradient.len() - 1) as f64;
+            let i = (at.floor() as usize).min(gradient.len() - 2);
+            let (Rgb(from), Rgb(to)) = (gradient[i], gradient[i + 1]);
+            let t = at - i as f64;
+
+            Rgb([0, 1, 2].map(|c| {
+                (f64::from(from[c]) + (f64::from(to[c]) - f64::from(from[c])) * t).round() as u8
+            }))
         }
-        None => {
-            // If a key doesn't exist, add it
-            let mut vec = Vec::with_capacity(4);
-            vec.push((dx, dy));
+    }
+}
 
-            adj_list.insert((x, y), vec);
+// One pixel per square, the furthest square gets the end of the gradient
+pub fn distances_to_img(
+    maze: &Maze,
+    distances: &[Option<usize>],
+    style: &HeatmapStyle,
+) -> DynamicImage {
+    let furthest = distances
+        .iter()
+        .flatten()
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let img = RgbImage::from_fn(maze.width() as u32, maze.height() as u32, |x, y| {
+        let square = (y as usize, x as usize);
+
+        match distances.get(maze.index(square)).copied().flatten() {
+            _ if !maze.is_open(square) => style.wall,
+            Some(distance) => gradient_color(&style.gradient, distance as f64 / furthest as f64),
+            None => style.unreachable,
         }
-    };
+    });
+
+    DynamicImage::ImageRgb8(img)
+}
+
+pub fn output_distance_map(
+    maze: &Maze,
+    distances: &[Option<usize>],
+    style: &HeatmapStyle,
+    output: &str,
+) -> Result<(), MazeError> {
+    distances_to_img(maze, distances, style)
+        .save(output)
+        .map_err(MazeError::ImageEncode)
+}
+
+pub fn maze_to_adjacency_list(maze: &Maze) -> AdjacencyList {
+    let mut adj_list = HashMap::with_capacity(maze.len());
+
+    // No path from a WALL to anywhere else,
+    //  check if there is a path in the 4 possible directions
+    for square in maze.open_squares() {
+        adj_list.insert(square, maze.neighbors(square).collect());
+    }
+
+    adj_list
 }
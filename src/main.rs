@@ -1,55 +1,1083 @@
 extern crate image;
 extern crate maze_solver;
 
-use std::env::args;
+use std::env;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use maze_solver::img_to_vec;
-use maze_solver::maze_to_adjacency_list;
-use maze_solver::output_maze_solution;
-use maze_solver::parse_image;
-use maze_solver::print_img;
+use image::imageops::FilterType;
+use image::{DynamicImage, Rgb};
 
-use maze_solver::algorithms::backtracking_breadth_first;
-use maze_solver::algorithms::breadth_first;
-use maze_solver::algorithms::depth_first;
-use maze_solver::algorithms::simple_always_left;
-use maze_solver::algorithms::wall_follower;
+use maze_solver::algorithms::distance_map_from_start;
+use maze_solver::analysis;
+use maze_solver::animation::{self, AnimationFormat, AnimationStyle};
+use maze_solver::batch::{self, BatchOptions, BatchOutput};
+use maze_solver::distances_to_img;
+use maze_solver::generators::{self, Algorithm};
+use maze_solver::geometry::render_solution_scaled;
+use maze_solver::input::{self, InputConfig, LoadedMaze};
+use maze_solver::preprocess::PreprocessConfig;
+use maze_solver::render::save_image;
+use maze_solver::svg::{self, SvgStyle};
+use maze_solver::terminal::{self, Overflow, TerminalMode, TerminalStyle};
+use maze_solver::text::{self, Charset};
+use maze_solver::validate::{validate_path_with, Checks, Violation};
+use maze_solver::{output_maze_solution_with_style, OutputFormat, RenderStyle, SolutionColor};
+use maze_solver::{AppliedThreshold, HeatmapStyle, MarkerColors};
+use maze_solver::{CellGeometry, Maze, MazeError, Pos, Registry, SolveResult};
+use maze_solver::{ThresholdConfig, ThresholdMethod};
+
+const USAGE: &str = "\
+Usage: maze_solver <COMMAND> [OPTIONS]
+
+Commands:
+  solve <INPUT>      Solve a maze and print what each algorithm found
+  generate           Generate a maze
+  render <INPUT>     Draw a maze, and its solution with -a
+  analyze <INPUT>    Print facts about a maze
+  convert <INPUT>    Write a maze in another format
+  bench <INPUT>      Time the algorithms on a maze
+  batch <INPUT>      Solve every maze in a directory, or matching a glob
+  help               Print this
+
+Input:
+  INPUT is an image, or a text maze if it ends with .txt
+  --threshold <T>    Pixels darker than this are walls: N for every channel,
+                     R,G,B, luma:N, otsu or adaptive [default: 50]
+  --alpha-walls      Transparent pixels are walls
+  --photo            Clean up a photo or a scan first: denoise, seal the walls,
+                     straighten and crop it [--threshold default: otsu]
+  --thick            The walls and the corridors are more than a pixel wide,
+                     image outputs draw the solution over the input
+  --markers          Green and blue pixels are the start and the end
+  --start <L,C>      Start on line L, column C
+  --end <L,C>        End on line L, column C
+
+Solving:
+  -a, --algorithm <NAME>  Can be given more than once, `all` for every one
+                          [default: breadth_first]
+  --runs <N>              How many times bench runs each algorithm [default: 10]
+  --validate              Check that each path is legal, and whether it's
+                          as short as can be
+
+Batch:
+  --threads <N>        [default: one per CPU core]
+  --summary <PATH>     CSV, or JSON if it ends with .json [default: CSV on stdout]
+  --out-dir <DIR>      Write the solutions there, in the same tree as the input
+  --next-to-input      Write the solutions next to the mazes
+
+Generating:
+  -a, --algorithm <NAME>  [default: recursive_backtracker]
+  --width <N>, --height <N>  In cells [default: 20]
+  --seed <N>              [default: the current time]
+
+Output:
+  -o, --output <PATH>  png, jpg, bmp, ppm... images, svg, gif, apng, txt,
+                       or - for the terminal (stdout with --format txt or svg)
+  --format <FORMAT>    When the extension isn't enough (e.g. apng in a .png)
+  --scale <N>          Pixels per square [default: 1, 4 for animations]
+  --explored           Draw the squares the algorithm went through
+  --heatmap            render colors the squares by how far they are from the start
+  --classic            Write text mazes in the +--+ style
+  --box                Draw walls with lines in the terminal
+  --columns <N>        Width of the terminal [default: its width, $COLUMNS or 80]
+  --steps <N>          Squares added per animation frame [default: 1]
+  --thickness <N>      Width of the path in pixels [default: the whole square]
+  --gradient <RRGGBB,...>  Color the path from the start to the end
+  --wall-color, --open-color, --path-color, --explored-color <RRGGBB>
+
+Exit codes:
+  0 success, 1 some mazes of a batch failed, 2 bad arguments, 3 the input couldn't be read,
+  4 the output couldn't be written, 5 malformed maze,
+  6 no start or end, 7 the end can't be reached,
+  8 another file (text maze, summary, directory...) couldn't be read or written";
+
+// Flags followed by a value
+const VALUE_FLAGS: &[&str] = &[
+    "algorithm",
+    "output",
+    "format",
+    "threshold",
+    "start",
+    "end",
+    "width",
+    "height",
+    "seed",
+    "scale",
+    "columns",
+    "steps",
+    "runs",
+    "wall-color",
+    "open-color",
+    "path-color",
+    "explored-color",
+    "thickness",
+    "gradient",
+    "threads",
+    "summary",
+    "out-dir",
+];
+
+const SWITCHES: &[&str] = &[
+    "thick",
+    "markers",
+    "explored",
+    "classic",
+    "box",
+    "validate",
+    "alpha-walls",
+    "photo",
+    "heatmap",
+    "next-to-input",
+    "help",
+];
 
 fn main() {
-    let img = parse_image(&mut args());
-    let maze = img_to_vec(&img);
-    print_img(&img);
-
-    output_maze_solution(
-        &img,                       // Reference to the image
-        simple_always_left(&maze),  // Algorithm
-        "examples/always_left.png"  // Output image
-    );
+    if let Err(e) = run(env::args().skip(1).collect()) {
+        eprintln!("Error: {}", e);
 
-    output_maze_solution(
-        &img,
-        wall_follower(&maze),
-        "examples/wall_follower.png"
-    );
+        if let CliError::Usage(_) = e {
+            eprintln!("Try `maze_solver help`");
+        }
 
-    // For the Depth and Breadth first algorithms:
-    let adj_list = maze_to_adjacency_list(&maze);
+        process::exit(exit_code(&e));
+    }
+}
 
-    output_maze_solution(
-        &img,
-        depth_first(&maze, &adj_list),
-        "examples/depth_first.png",
-    );
+// ------
+// Errors
+// ------
+#[derive(Debug)]
+enum CliError {
+    // The arguments don't make sense
+    Usage(String),
+    Maze(MazeError),
+    // How many entries of a batch failed, the details are in the summary
+    Batch(usize),
+}
 
-    output_maze_solution(
-        &img,
-        breadth_first(&maze, &adj_list),
-        "examples/breadth_first.png",
+impl From<MazeError> for CliError {
+    fn from(error: MazeError) -> Self {
+        CliError::Maze(error)
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}", message),
+            CliError::Maze(e) => write!(f, "{}", e),
+            CliError::Batch(failed) => write!(f, "{} solves failed, see the summary", failed),
+        }
+    }
+}
+
+fn usage<T>(message: String) -> Result<T, CliError> {
+    Err(CliError::Usage(message))
+}
+
+// The binary is the only place where errors turn into exit codes
+fn exit_code(error: &CliError) -> i32 {
+    let error = match error {
+        CliError::Usage(_) => return 2,
+        CliError::Batch(_) => return 1,
+        CliError::Maze(error) => error,
+    };
+
+    match error {
+        MazeError::MissingArgument | MazeError::ZeroThickness => 2,
+        MazeError::ImageDecode(_) => 3,
+        MazeError::ImageEncode(_) => 4,
+        MazeError::EmptyMaze
+        | MazeError::RaggedRows { .. }
+        | MazeError::ZeroCost(_)
+        | MazeError::Parse { .. } => 5,
+        MazeError::NoStart | MazeError::NoEnd | MazeError::BlockedSquare(_) => 6,
+        MazeError::Unreachable => 7,
+        MazeError::Io(_) => 8,
+    }
+}
+
+// ---------
+// Arguments
+// ---------
+struct Args {
+    positional: Vec<String>,
+    // In the order they were given
+    values: Vec<(String, String)>,
+    switches: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: Vec<String>) -> Result<Self, CliError> {
+        let mut parsed = Args {
+            positional: Vec::new(),
+            values: Vec::new(),
+            switches: Vec::new(),
+        };
+
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.strip_prefix("--") {
+                Some(flag) => match flag.split_once('=') {
+                    Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                    None => (flag.to_string(), None),
+                },
+                None => match arg.as_str() {
+                    "-a" => (String::from("algorithm"), None),
+                    "-o" => (String::from("output"), None),
+                    "-h" => (String::from("help"), None),
+                    // `-` alone is the terminal
+                    _ if arg.starts_with('-') && arg != "-" => {
+                        return usage(format!("unknown flag `{}`", arg))
+                    }
+                    _ => {
+                        parsed.positional.push(arg);
+                        continue;
+                    }
+                },
+            };
+
+            if VALUE_FLAGS.contains(&flag.as_str()) {
+                let value = match inline.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => return usage(format!("--{} needs a value", flag)),
+                };
+
+                parsed.values.push((flag, value));
+            } else if SWITCHES.contains(&flag.as_str()) && inline.is_none() {
+                parsed.switches.push(flag);
+            } else {
+                return usage(format!("unknown flag `{}`", arg));
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    // The last one given
+    fn value(&self, flag: &str) -> Option<&str> {
+        self.all(flag).pop()
+    }
+
+    fn all(&self, flag: &str) -> Vec<&str> {
+        self.values
+            .iter()
+            .filter(|(name, _)| name == flag)
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    fn switch(&self, flag: &str) -> bool {
+        self.switches.iter().any(|name| name == flag)
+    }
+
+    fn parse_value<T: FromStr>(&self, flag: &str) -> Result<Option<T>, CliError> {
+        match self.value(flag) {
+            Some(value) => match value.parse() {
+                Ok(value) => Ok(Some(value)),
+                Err(_) => usage(format!("invalid value `{}` for --{}", value, flag)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn color(&self, flag: &str, default: Rgb<u8>) -> Result<Rgb<u8>, CliError> {
+        match self.value(flag) {
+            Some(value) => match parse_color(value) {
+                Some(color) => Ok(color),
+                None => usage(format!("--{} takes a color like ff0000", flag)),
+            },
+            None => Ok(default),
+        }
+    }
+
+    fn square(&self, flag: &str) -> Result<Option<Pos>, CliError> {
+        match self.value(flag) {
+            Some(value) => match parse_square(value) {
+                Some(square) => Ok(Some(square)),
+                None => usage(format!("--{} takes a line and a column, like 3,4", flag)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn input(&self) -> Result<&str, CliError> {
+        match self.positional.first() {
+            Some(input) => Ok(input),
+            None => Err(CliError::Maze(MazeError::MissingArgument)),
+        }
+    }
+
+    // -o, or the second positional argument
+    fn output(&self) -> Option<&str> {
+        self.value("output")
+            .or_else(|| self.positional.get(1).map(|output| output.as_str()))
+    }
+}
+
+// RRGGBB, with or without a #
+fn parse_color(value: &str) -> Option<Rgb<u8>> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+
+    Some(Rgb([channel(0)?, channel(2)?, channel(4)?]))
+}
+
+// LINE,COLUMN
+fn parse_square(value: &str) -> Option<Pos> {
+    let (line, col) = value.split_once(',')?;
+
+    Some((line.trim().parse().ok()?, col.trim().parse().ok()?))
+}
+
+// --------
+// Commands
+// --------
+fn run(args: Vec<String>) -> Result<(), CliError> {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest.to_vec()),
+        None => return Err(CliError::Maze(MazeError::MissingArgument)),
+    };
+
+    let parsed = Args::parse(rest)?;
+
+    if parsed.switch("help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    match command {
+        "solve" => solve(&parsed),
+        "generate" => generate(&parsed),
+        "render" => render(&parsed),
+        "analyze" => analyze(&parsed),
+        "convert" => convert(&parsed),
+        "bench" => bench(&parsed),
+        "batch" => run_batch(&parsed),
+        "help" | "-h" | "--help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        // An image alone, like before there were commands
+        _ if !command.starts_with('-') && Path::new(command).exists() => solve(&Args::parse(args)?),
+        _ => usage(format!("unknown command `{}`", command)),
+    }
+}
+
+fn solve(args: &Args) -> Result<(), CliError> {
+    let loaded = load_input(args)?;
+    let maze = &loaded.maze;
+    let registry = Registry::with_builtin();
+    let names = solver_names(args, &registry)?;
+
+    for name in &names {
+        let result = run_solver(&registry, name, maze)?;
+
+        print_result(name, &result);
+
+        if let (true, Some(path)) = (args.switch("validate"), &result.path) {
+            let checks = Checks {
+                simple: true,
+                optimal: true,
+            };
+
+            // Only some of the solvers look for the shortest path,
+            //  a longer one is still a solution
+            match validate_path_with(maze, path, checks)?.violation {
+                Some(violation @ Violation::NotOptimal { .. }) => {
+                    println!("  valid, not optimal: {}", violation)
+                }
+                Some(violation) => println!("  invalid path: {}", violation),
+                None => println!("  valid and optimal"),
+            }
+        }
+
+        if let Some(output) = args.output() {
+            // One file per algorithm when there's more than one
+            let output = if names.len() > 1 {
+                suffixed(output, name)
+            } else {
+                output.to_string()
+            };
+
+            write_output(args, maze, Some(&result), thick_image(&loaded), &output)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn generate(args: &Args) -> Result<(), CliError> {
+    let name = args.value("algorithm").unwrap_or("recursive_backtracker");
+    let algorithm: Algorithm = match name.parse() {
+        Ok(algorithm) => algorithm,
+        Err(()) => {
+            let names: Vec<&str> = Algorithm::ALL.iter().map(|a| a.name()).collect();
+            return usage(format!(
+                "unknown generator `{}`, try one of: {}",
+                name,
+                names.join(", ")
+            ));
+        }
+    };
+
+    let width = args.parse_value("width")?.unwrap_or(20);
+    let height = args.parse_value("height")?.unwrap_or(20);
+    let seed = match args.parse_value("seed")? {
+        Some(seed) => seed,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64),
+    };
+
+    let maze = generators::generate(algorithm, width, height, seed)?;
+    let output = args.output().unwrap_or("-");
+
+    write_output(args, &maze, None, None, output)?;
+
+    if output != "-" {
+        println!(
+            "{} maze of {}x{} cells, seed {}, written to {}",
+            algorithm.name(),
+            width,
+            height,
+            seed,
+            output
+        );
+    }
+
+    Ok(())
+}
+
+// To the terminal unless there's an output
+fn render(args: &Args) -> Result<(), CliError> {
+    let loaded = load_input(args)?;
+    let maze = &loaded.maze;
+
+    if args.switch("heatmap") {
+        return heatmap(args, maze);
+    }
+
+    let registry = Registry::with_builtin();
+
+    let result = match args.value("algorithm") {
+        Some(name) => Some(run_solver(&registry, name, maze)?),
+        None => None,
+    };
+
+    write_output(
+        args,
+        maze,
+        result.as_ref(),
+        thick_image(&loaded),
+        args.output().unwrap_or("-"),
+    )
+}
+
+// How far every square is from the start, as an image
+fn heatmap(args: &Args, maze: &Maze) -> Result<(), CliError> {
+    let output = match args.output() {
+        Some(output) if output != "-" => output,
+        _ => return usage(String::from("--heatmap needs an image output")),
+    };
+
+    let distances = distance_map_from_start(maze)?;
+    let style = HeatmapStyle {
+        wall: args.color("wall-color", Rgb([0, 0, 0]))?,
+        ..HeatmapStyle::default()
+    };
+
+    let scale: u32 = args.parse_value("scale")?.unwrap_or(1).max(1);
+    let img = distances_to_img(maze, &distances, &style);
+    let img = img.resize(
+        img.width() * scale,
+        img.height() * scale,
+        FilterType::Nearest,
     );
 
-    output_maze_solution(
-        &img,
-        backtracking_breadth_first(&maze, &adj_list),
-        "examples/backtracking_breadth_first.png",
+    img.save(output).map_err(MazeError::ImageEncode)?;
+
+    Ok(())
+}
+
+// JSON with --format json or a .json output, otherwise text
+fn analyze(args: &Args) -> Result<(), CliError> {
+    let maze = load_maze(args)?;
+    let report = analysis::analyze(&maze);
+
+    let json = match args.value("format") {
+        Some(format) => format.eq_ignore_ascii_case("json"),
+        None => args.output().and_then(extension).as_deref() == Some("json"),
+    };
+
+    if json {
+        return match args.output() {
+            Some(output) if output != "-" => {
+                std::fs::write(output, report.to_json()).map_err(|e| MazeError::Io(e).into())
+            }
+            _ => {
+                print!("{}", report.to_json());
+                Ok(())
+            }
+        };
+    }
+
+    let optional = |value: Option<String>| value.unwrap_or_else(|| String::from("none"));
+
+    println!("Size: {}x{} squares", report.width, report.height);
+    println!(
+        "Open squares: {} ({} walls)",
+        report.open_squares,
+        maze.len() - report.open_squares
+    );
+    println!(
+        "Dead ends: {}, junctions: {}, corridors: {}",
+        report.dead_ends, report.junctions, report.corridors
+    );
+    println!("Longest corridor: {} steps", report.longest_corridor);
+    println!("River: {:.2} squares per dead end branch", report.river);
+    println!(
+        "Components: {}, loops: {} ({})",
+        report.components,
+        report.cyclomatic_number,
+        if report.perfect {
+            "perfect"
+        } else {
+            "not perfect"
+        }
     );
+    println!("Start: {:?}, end: {:?}", maze.start().ok(), maze.end().ok());
+    println!(
+        "Solution: {} squares, {} of the open squares",
+        optional(report.solution_length.map(|length| length.to_string())),
+        optional(
+            report
+                .solution_ratio
+                .map(|ratio| format!("{:.1}%", ratio * 100.0))
+        )
+    );
+
+    Ok(())
+}
+
+fn convert(args: &Args) -> Result<(), CliError> {
+    let maze = load_maze(args)?;
+
+    match args.output() {
+        Some(output) => write_output(args, &maze, None, None, output),
+        None => usage(String::from("convert needs an output")),
+    }
+}
+
+fn bench(args: &Args) -> Result<(), CliError> {
+    let maze = load_maze(args)?;
+    let registry = Registry::with_builtin();
+    let runs: usize = args.parse_value("runs")?.unwrap_or(10).max(1);
+
+    let names = if args.value("algorithm").is_some() {
+        solver_names(args, &registry)?
+    } else {
+        registry.names()
+    };
+
+    for name in names {
+        let mut times = Vec::with_capacity(runs);
+
+        for _ in 0..runs {
+            let timer = Instant::now();
+
+            match run_solver(&registry, name, &maze) {
+                // Getting stuck is a result too
+                Ok(_) | Err(CliError::Maze(MazeError::Unreachable)) => {}
+                Err(e) => return Err(e),
+            }
+
+            times.push(timer.elapsed());
+        }
+
+        let total: Duration = times.iter().sum();
+
+        println!(
+            "{:<40} min {:>10.3} ms  mean {:>10.3} ms  max {:>10.3} ms",
+            name,
+            millis(*times.iter().min().unwrap()),
+            millis(total / runs as u32),
+            millis(*times.iter().max().unwrap()),
+        );
+    }
+
+    Ok(())
+}
+
+fn run_batch(args: &Args) -> Result<(), CliError> {
+    let pattern = args.input()?;
+    let registry = Registry::with_builtin();
+
+    let solvers: Vec<String> = solver_names(args, &registry)?
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    // Unknown names would fail on every maze
+    if let Some(name) = solvers.iter().find(|name| registry.get(name).is_none()) {
+        return usage(format!(
+            "unknown algorithm `{}`, try one of: {}",
+            name,
+            registry.names().join(", ")
+        ));
+    }
+
+    let output = match (args.value("out-dir"), args.switch("next-to-input")) {
+        (Some(_), true) => {
+            return usage(String::from(
+                "--out-dir and --next-to-input can't be used together",
+            ))
+        }
+        (Some(dir), false) => BatchOutput::Mirror {
+            root: batch::pattern_root(pattern),
+            into: dir.into(),
+        },
+        (None, true) => BatchOutput::NextToInput,
+        (None, false) => BatchOutput::None,
+    };
+
+    let options = BatchOptions {
+        solvers,
+        threads: args.parse_value("threads")?.unwrap_or(0),
+        output,
+        input: input_config(args)?,
+    };
+
+    let inputs = batch::find_inputs(pattern, &registry, &options)?;
+
+    if inputs.is_empty() {
+        return usage(format!("no mazes found in `{}`", pattern));
+    }
+
+    let entries = batch::solve_batch(&inputs, &registry, &options);
+
+    match args.value("summary") {
+        Some(summary) => {
+            let text = if extension(summary).as_deref() == Some("json") {
+                batch::summary_json(&entries)
+            } else {
+                batch::summary_csv(&entries)
+            };
+
+            std::fs::write(summary, text).map_err(MazeError::Io)?;
+        }
+        None => print!("{}", batch::summary_csv(&entries)),
+    }
+
+    match entries.iter().filter(|entry| entry.error.is_some()).count() {
+        0 => Ok(()),
+        failed => Err(CliError::Batch(failed)),
+    }
+}
+
+// -------
+// Helpers
+// -------
+fn load_maze(args: &Args) -> Result<Maze, CliError> {
+    Ok(load_input(args)?.maze)
+}
+
+fn load_input(args: &Args) -> Result<LoadedMaze, CliError> {
+    let config = input_config(args)?;
+    let loaded = input::load_maze(Path::new(args.input()?), &config)?;
+
+    // Only worth telling when it was worked out from the image
+    match loaded.applied {
+        Some(AppliedThreshold::Luminance(threshold))
+            if config.threshold.method == ThresholdMethod::Otsu =>
+        {
+            eprintln!("Threshold: luminance below {}", threshold)
+        }
+        Some(AppliedThreshold::Local { min, max }) => {
+            eprintln!("Threshold: luminance from {} to {}", min, max)
+        }
+        _ => {}
+    }
+
+    Ok(loaded)
+}
+
+// The image of a maze read with --thick, solutions are painted over it
+fn thick_image(loaded: &LoadedMaze) -> Option<(&DynamicImage, CellGeometry)> {
+    match (&loaded.image, loaded.geometry) {
+        (Some(img), Some(geometry)) if geometry != CellGeometry::PIXEL => Some((img, geometry)),
+        _ => None,
+    }
+}
+
+// How solve, batch and the others read their input
+fn input_config(args: &Args) -> Result<InputConfig, CliError> {
+    // Markers are pixels of the image, there's no telling where
+    //  they end up once the photo is straightened and cropped
+    if args.switch("photo") && args.switch("markers") {
+        return usage(String::from(
+            "--markers can't be used with --photo, try --start and --end",
+        ));
+    }
+
+    let transparent_walls = if args.switch("alpha-walls") {
+        Some(128)
+    } else {
+        None
+    };
+
+    let photo = if args.switch("photo") {
+        Some(PreprocessConfig {
+            threshold: ThresholdConfig {
+                method: match args.value("threshold") {
+                    Some(_) => threshold_method(args)?,
+                    None => ThresholdMethod::Otsu,
+                },
+                transparent_walls,
+            },
+            ..PreprocessConfig::default()
+        })
+    } else {
+        None
+    };
+
+    Ok(InputConfig {
+        threshold: ThresholdConfig {
+            method: threshold_method(args)?,
+            transparent_walls,
+        },
+        photo,
+        thick: args.switch("thick"),
+        markers: if args.switch("markers") {
+            Some(MarkerColors::default())
+        } else {
+            None
+        },
+        start: args.square("start")?,
+        end: args.square("end")?,
+    })
+}
+
+// N for every channel, R,G,B, luma:N, otsu or adaptive
+fn threshold_method(args: &Args) -> Result<ThresholdMethod, CliError> {
+    let value = match args.value("threshold") {
+        Some(value) => value.to_ascii_lowercase(),
+        None => return Ok(ThresholdConfig::default().method),
+    };
+
+    let invalid = || usage(format!("invalid value `{}` for --threshold", value));
+
+    let method = match value.as_str() {
+        "otsu" => ThresholdMethod::Otsu,
+        "adaptive" => ThresholdMethod::Adaptive { radius: 7, k: 0.2 },
+        _ => {
+            if let Some(luma) = value.strip_prefix("luma:") {
+                match luma.parse() {
+                    Ok(luma) => ThresholdMethod::Luminance(luma),
+                    Err(_) => return invalid(),
+                }
+            } else {
+                let channels: Result<Vec<u8>, _> = value
+                    .split(',')
+                    .map(|channel| channel.trim().parse())
+                    .collect();
+
+                match channels.as_deref() {
+                    Ok(&[all]) => ThresholdMethod::Channels {
+                        red: all,
+                        green: all,
+                        blue: all,
+                    },
+                    Ok(&[red, green, blue]) => ThresholdMethod::Channels { red, green, blue },
+                    _ => return invalid(),
+                }
+            }
+        }
+    };
+
+    Ok(method)
+}
+
+fn solver_names<'a>(args: &'a Args, registry: &Registry) -> Result<Vec<&'a str>, CliError> {
+    let mut names = args.all("algorithm");
+
+    if names.is_empty() {
+        names.push("breadth_first");
+    }
+
+    if names.contains(&"all") {
+        return Ok(registry.names());
+    }
+
+    Ok(names)
+}
+
+fn run_solver(registry: &Registry, name: &str, maze: &Maze) -> Result<SolveResult, CliError> {
+    match registry.get(name) {
+        Some(solver) => Ok(solver.solve(maze)?),
+        None => usage(format!(
+            "unknown algorithm `{}`, try one of: {}",
+            name,
+            registry.names().join(", ")
+        )),
+    }
+}
+
+fn print_result(name: &str, result: &SolveResult) {
+    match &result.path {
+        Some(path) => println!(
+            "{}: path of {} squares, cost {}, {} squares explored, {:.3} ms",
+            name,
+            path.len(),
+            result.cost.unwrap_or(0),
+            result.explored.len(),
+            millis(result.stats.elapsed)
+        ),
+        None => println!(
+            "{}: no path, {} squares explored, {:.3} ms",
+            name,
+            result.explored.len(),
+            millis(result.stats.elapsed)
+        ),
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn extension(path: &str) -> Option<String> {
+    Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase())
+}
+
+// maze.png, breadth_first -> maze_breadth_first.png
+fn suffixed(output: &str, suffix: &str) -> String {
+    let path = Path::new(output);
+
+    match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(extension)) => path
+            .with_file_name(format!(
+                "{}_{}.{}",
+                stem.to_string_lossy(),
+                suffix,
+                extension.to_string_lossy()
+            ))
+            .to_string_lossy()
+            .into_owned(),
+        _ => format!("{}_{}", output, suffix),
+    }
+}
+
+// To stdout when it's -
+fn write_text(output: &str, text: &str) -> Result<(), CliError> {
+    if output == "-" {
+        return io::stdout()
+            .lock()
+            .write_all(text.as_bytes())
+            .map_err(|e| MazeError::Io(e).into());
+    }
+
+    std::fs::write(output, text).map_err(|e| MazeError::Io(e).into())
+}
+
+// Colors of the squares, for every output but text
+struct Colors {
+    wall: Rgb<u8>,
+    open: Rgb<u8>,
+    path: Rgb<u8>,
+    explored: Rgb<u8>,
+}
+
+impl Colors {
+    fn from_args(args: &Args) -> Result<Self, CliError> {
+        Ok(Colors {
+            wall: args.color("wall-color", Rgb([0, 0, 0]))?,
+            open: args.color("open-color", Rgb([255, 255, 255]))?,
+            path: args.color("path-color", Rgb([255, 0, 0]))?,
+            explored: args.color("explored-color", Rgb([150, 190, 255]))?,
+        })
+    }
+}
+
+// The format comes from --format, or from the extension. Images of thick
+//  mazes are drawn over the image they were read from
+fn write_output(
+    args: &Args,
+    maze: &Maze,
+    result: Option<&SolveResult>,
+    thick: Option<(&DynamicImage, CellGeometry)>,
+    output: &str,
+) -> Result<(), CliError> {
+    let colors = Colors::from_args(args)?;
+    let solved = result.is_some();
+    let empty = SolveResult::default();
+    let result = result.unwrap_or(&empty);
+
+    let solution = result.path.as_deref().unwrap_or(&[]);
+    let explored = if args.switch("explored") {
+        &result.explored[..]
+    } else {
+        &[]
+    };
+
+    let format = match args.value("format") {
+        Some(format) => format.to_ascii_lowercase(),
+        None if output == "-" => String::from("terminal"),
+        None => extension(output).unwrap_or_default(),
+    };
+
+    let scale: u32 = args.parse_value("scale")?.unwrap_or(0);
+
+    // Only text goes to stdout
+    if output == "-" && !["terminal", "txt", "svg"].contains(&format.as_str()) {
+        return usage(format!(
+            "{} can't be written to stdout, give a file",
+            format
+        ));
+    }
+
+    match format.as_str() {
+        "terminal" => {
+            // The terminal has colors of its own, only the ones given change
+            let defaults = TerminalStyle::default();
+
+            let style = TerminalStyle {
+                mode: if args.switch("box") {
+                    TerminalMode::BoxDrawing
+                } else {
+                    TerminalMode::HalfBlocks
+                },
+                overflow: Overflow::Downsample,
+                columns: args.parse_value("columns")?,
+                wall: args.color("wall-color", defaults.wall)?,
+                open: args.color("open-color", defaults.open)?,
+                explored: args.color("explored-color", defaults.explored)?,
+                solution: args.color("path-color", defaults.solution)?,
+                ..defaults
+            };
+
+            terminal::render(&mut io::stdout().lock(), maze, solution, explored, &style)
+                .map_err(MazeError::Io)?;
+        }
+        "txt" => {
+            let text = if args.switch("classic") {
+                match text::maze_to_classic(maze, solution, 2) {
+                    Some(text) => text,
+                    None => return usage(String::from("the maze isn't made of cells")),
+                }
+            } else {
+                text::maze_to_text(maze, solution, &Charset::default())
+            };
+
+            write_text(output, &text)?;
+        }
+        "svg" => {
+            let style = SvgStyle {
+                cell_size: f64::from(scale.max(1)) * 10.0,
+                wall: colors.wall,
+                background: colors.open,
+                path: colors.path,
+                path_width: f64::from(scale.max(1)) * 4.0,
+                explored: if args.switch("explored") {
+                    Some(colors.explored)
+                } else {
+                    None
+                },
+                ..SvgStyle::default()
+            };
+
+            write_text(output, &svg::maze_to_svg(maze, solution, explored, &style))?;
+        }
+        "gif" | "apng" => {
+            let style = AnimationStyle {
+                steps_per_frame: args.parse_value("steps")?.unwrap_or(1),
+                scale: if scale == 0 { 4 } else { scale },
+                wall: colors.wall,
+                open: colors.open,
+                visited: colors.explored,
+                path: colors.path,
+                ..AnimationStyle::default()
+            };
+
+            let format = if format == "gif" {
+                AnimationFormat::Gif
+            } else {
+                AnimationFormat::Apng
+            };
+
+            let file = std::fs::File::create(output).map_err(MazeError::Io)?;
+
+            animation::write_animation(maze, result, &style, format, io::BufWriter::new(file))?;
+        }
+        _ => {
+            let gradient = match args.value("gradient") {
+                Some(value) => match value.split(',').map(parse_color).collect() {
+                    Some(gradient) => Some(gradient),
+                    None => {
+                        return usage(String::from("--gradient takes colors like ff0000,0000ff"))
+                    }
+                },
+                None => None,
+            };
+
+            // The extension can be anything the image crate knows (e.g. tiff),
+            //  --format has to be one of ours
+            let image_format = match (OutputFormat::from_name(&format), args.value("format")) {
+                (None, Some(name)) => {
+                    return usage(format!(
+                        "unknown format `{}`, try one of: terminal, txt, svg, gif, apng, \
+                         png, jpg, bmp, webp, ppm",
+                        name
+                    ))
+                }
+                (image_format, _) => image_format,
+            };
+
+            if let Some((img, geometry)) = thick {
+                let img = render_solution_scaled(img, solution, geometry)?;
+
+                return Ok(save_image(&img, image_format, output)?);
+            }
+
+            // The markers only say something next to a solution
+            let (start, end) = if solved {
+                (RenderStyle::default().start, RenderStyle::default().end)
+            } else {
+                (colors.open, colors.open)
+            };
+
+            let style = RenderStyle {
+                wall: colors.wall,
+                path: colors.open,
+                explored: colors.explored,
+                solution: match gradient {
+                    Some(gradient) => SolutionColor::Gradient(gradient),
+                    None => SolutionColor::Solid(colors.path),
+                },
+                start,
+                end,
+                scale: scale.max(1),
+                thickness: args.parse_value("thickness")?.unwrap_or(0),
+                format: image_format,
+            };
+
+            output_maze_solution_with_style(maze, solution, explored, &style, output)?;
+        }
+    }
+
+    Ok(())
 }
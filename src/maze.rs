@@ -0,0 +1,327 @@
+use crate::constants::PATH;
+use crate::MazeError;
+use crate::Pos;
+
+use std::convert::TryFrom;
+use std::ops::Index;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Down,
+    Right,
+    Up,
+}
+
+impl Direction {
+    // The order the adjacency list has always used
+    pub const ALL: [Direction; 4] = [
+        Direction::Down,
+        Direction::Right,
+        Direction::Up,
+        Direction::Left,
+    ];
+}
+
+// A rectangular grid of WALL and PATH squares, stored line by line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Maze {
+    cells: Vec<u8>,
+    width: usize,
+    height: usize,
+    start: Option<Pos>,
+    end: Option<Pos>,
+    // What it costs to step on each square, None if every square costs 1
+    costs: Option<Vec<u32>>,
+}
+
+impl Maze {
+    // Every square is given line by line, the start and the end
+    //  are looked for on the borders
+    pub fn new(width: usize, height: usize, cells: Vec<u8>) -> Result<Self, MazeError> {
+        if width == 0 || height == 0 {
+            return Err(MazeError::EmptyMaze);
+        }
+
+        if cells.len() != width * height {
+            return Err(MazeError::RaggedRows {
+                row: cells.len() / width,
+                expected: width,
+                found: cells.len() % width,
+            });
+        }
+
+        let mut maze = Self {
+            cells,
+            width,
+            height,
+            start: None,
+            end: None,
+            costs: None,
+        };
+
+        maze.start = maze.find_start();
+        maze.end = maze.find_end();
+
+        Ok(maze)
+    }
+
+    pub fn from_rows(rows: &[Vec<u8>]) -> Result<Self, MazeError> {
+        let width = match rows.first() {
+            Some(row) if !row.is_empty() => row.len(),
+            _ => return Err(MazeError::EmptyMaze),
+        };
+
+        let mut cells = Vec::with_capacity(width * rows.len());
+
+        for (row, cols) in rows.iter().enumerate() {
+            if cols.len() != width {
+                return Err(MazeError::RaggedRows {
+                    row,
+                    expected: width,
+                    found: cols.len(),
+                });
+            }
+
+            cells.extend_from_slice(cols);
+        }
+
+        Self::new(width, rows.len(), cells)
+    }
+
+    // Gives every square a cost, line by line. Costs start at 1 so that
+    //  the A* heuristics never overestimate
+    pub fn with_costs(mut self, costs: Vec<u32>) -> Result<Self, MazeError> {
+        if costs.len() != self.cells.len() {
+            return Err(MazeError::RaggedRows {
+                row: costs.len() / self.width,
+                expected: self.width,
+                found: costs.len() % self.width,
+            });
+        }
+
+        if let Some(i) = costs.iter().position(|&cost| cost == 0) {
+            return Err(MazeError::ZeroCost(self.pos(i)));
+        }
+
+        self.costs = Some(costs);
+
+        Ok(self)
+    }
+
+    // Replaces the start found on the borders, it can be any open square
+    pub fn with_start(mut self, start: Pos) -> Result<Self, MazeError> {
+        if !self.is_open(start) {
+            return Err(MazeError::BlockedSquare(start));
+        }
+
+        self.start = Some(start);
+
+        Ok(self)
+    }
+
+    // Replaces the end found on the borders, it can be any open square
+    pub fn with_end(mut self, end: Pos) -> Result<Self, MazeError> {
+        if !self.is_open(end) {
+            return Err(MazeError::BlockedSquare(end));
+        }
+
+        self.end = Some(end);
+
+        Ok(self)
+    }
+
+    pub fn is_weighted(&self) -> bool {
+        self.costs.is_some()
+    }
+
+    // What it costs to step on a square
+    pub fn cost(&self, pos: Pos) -> u32 {
+        match &self.costs {
+            Some(costs) => costs[self.index(pos)],
+            None => 1,
+        }
+    }
+
+    // Sum of the costs of every square stepped on, the start is free
+    pub fn path_cost(&self, path: &[Pos]) -> u64 {
+        path.iter()
+            .skip(1)
+            .map(|&square| u64::from(self.cost(square)))
+            .sum()
+    }
+
+    pub fn to_rows(&self) -> Vec<Vec<u8>> {
+        self.cells
+            .chunks(self.width)
+            .map(|row| row.to_vec())
+            .collect()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // Number of squares, walls included
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    // A maze can't be built without squares, but clippy wants it
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn cells(&self) -> &[u8] {
+        &self.cells
+    }
+
+    pub fn start(&self) -> Result<Pos, MazeError> {
+        self.start.ok_or(MazeError::NoStart)
+    }
+
+    pub fn end(&self) -> Result<Pos, MazeError> {
+        self.end.ok_or(MazeError::NoEnd)
+    }
+
+    // Position of a square in the storage, squares are stored line by line
+    pub fn index(&self, (line, col): Pos) -> usize {
+        line * self.width + col
+    }
+
+    pub fn pos(&self, index: usize) -> Pos {
+        (index / self.width, index % self.width)
+    }
+
+    pub fn contains(&self, (line, col): Pos) -> bool {
+        line < self.height && col < self.width
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<u8> {
+        if self.contains(pos) {
+            Some(self.cells[self.index(pos)])
+        } else {
+            None
+        }
+    }
+
+    pub fn is_open(&self, pos: Pos) -> bool {
+        self.get(pos) == Some(PATH)
+    }
+
+    // The square next to `pos`, if it's still inside the maze
+    pub fn step(&self, (line, col): Pos, direction: Direction) -> Option<Pos> {
+        let next = match direction {
+            Direction::Left => (line, col.checked_sub(1)?),
+            Direction::Down => (line + 1, col),
+            Direction::Right => (line, col + 1),
+            Direction::Up => (line.checked_sub(1)?, col),
+        };
+
+        if self.contains(next) {
+            Some(next)
+        } else {
+            None
+        }
+    }
+
+    // Same as above, but only if the square isn't a WALL
+    pub fn open_step(&self, pos: Pos, direction: Direction) -> Option<Pos> {
+        self.step(pos, direction).filter(|&next| self.is_open(next))
+    }
+
+    // Open squares next to `pos`: down, right, up then left
+    pub fn neighbors(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        Direction::ALL
+            .iter()
+            .filter_map(move |&direction| self.open_step(pos, direction))
+    }
+
+    // Every open square, line by line
+    pub fn open_squares(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|&(_, &square)| square == PATH)
+            .map(move |(i, _)| self.pos(i))
+    }
+
+    // Every open square on the borders, clockwise from the top left corner
+    pub fn border_openings(&self) -> Vec<Pos> {
+        let (last_line, last_col) = (self.height - 1, self.width - 1);
+
+        let border: Vec<Pos> = if self.height == 1 {
+            (0..self.width).map(|col| (0, col)).collect()
+        } else if self.width == 1 {
+            (0..self.height).map(|line| (line, 0)).collect()
+        } else {
+            let top = (0..self.width).map(|col| (0, col));
+            let right = (1..self.height).map(|line| (line, last_col));
+            let bottom = (0..last_col).rev().map(|col| (last_line, col));
+            let left = (1..last_line).rev().map(|line| (line, 0));
+
+            top.chain(right).chain(bottom).chain(left).collect()
+        };
+
+        border
+            .into_iter()
+            .filter(|&pos| self.is_open(pos))
+            .collect()
+    }
+
+    fn find_start(&self) -> Option<Pos> {
+        // Check the top, then the left
+        let top = (0..self.width).map(|col| (0, col));
+        let left = (0..self.height).map(|line| (line, 0));
+
+        top.chain(left).find(|&pos| self.is_open(pos))
+    }
+
+    fn find_end(&self) -> Option<Pos> {
+        // Check the bottom, then the right
+        let bottom = (0..self.width).map(|col| (self.height - 1, col));
+        let right = (0..self.height).map(|line| (line, self.width - 1));
+
+        bottom.chain(right).find(|&pos| self.is_open(pos))
+    }
+}
+
+impl Index<Pos> for Maze {
+    type Output = u8;
+
+    fn index(&self, pos: Pos) -> &u8 {
+        &self.cells[Maze::index(self, pos)]
+    }
+}
+
+impl TryFrom<Vec<Vec<u8>>> for Maze {
+    type Error = MazeError;
+
+    fn try_from(rows: Vec<Vec<u8>>) -> Result<Self, MazeError> {
+        Self::from_rows(&rows)
+    }
+}
+
+impl TryFrom<&[Vec<u8>]> for Maze {
+    type Error = MazeError;
+
+    fn try_from(rows: &[Vec<u8>]) -> Result<Self, MazeError> {
+        Self::from_rows(rows)
+    }
+}
+
+impl From<&Maze> for Vec<Vec<u8>> {
+    fn from(maze: &Maze) -> Self {
+        maze.to_rows()
+    }
+}
+
+impl From<Maze> for Vec<Vec<u8>> {
+    fn from(maze: Maze) -> Self {
+        maze.to_rows()
+    }
+}
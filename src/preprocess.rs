@@ -0,0 +1,512 @@
+use image::{DynamicImage, GrayImage, Luma};
+
+use crate::constants::{PATH, WALL};
+use crate::data_structures::Queue;
+use crate::threshold::{img_to_vec_with_config, ThresholdConfig, ThresholdMethod};
+use crate::{Maze, MazeError};
+
+// What to do to a photo or a scan before it becomes a maze. The steps run
+//  in the order of the fields. Made for walls a few pixels thick, the
+//  filters would erase the walls of a pixel-perfect maze
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreprocessConfig {
+    // Radius of the median filter run on the greyscale image, 0 to skip it
+    pub denoise: u32,
+    pub threshold: ThresholdConfig,
+    // Removes the dark specks this big (morphological opening of the walls)
+    pub open: u32,
+    // Seals the holes and the gaps in the walls this big (closing)
+    pub close: u32,
+    // Maps the four corners of the maze to the corners of the image, which
+    //  undoes the perspective and the rotation of a photo. Only for mazes
+    //  turned by less than 45 degrees
+    pub straighten: bool,
+    // Cuts the image to the walls of the maze
+    pub crop: bool,
+}
+
+impl Default for PreprocessConfig {
+    fn default() -> Self {
+        Self {
+            denoise: 1,
+            threshold: ThresholdConfig {
+                method: ThresholdMethod::Otsu,
+                transparent_walls: None,
+            },
+            open: 1,
+            close: 1,
+            straighten: true,
+            crop: true,
+        }
+    }
+}
+
+// Black walls on white, the maze alone when cropping or straightening:
+//  the walls away from the maze (page numbers, specks, the next puzzle)
+//  are left out then
+pub fn preprocess(img: &DynamicImage, config: &PreprocessConfig) -> Result<GrayImage, MazeError> {
+    let mut grey = img.to_luma8();
+
+    // The grey image has no alpha left, transparent walls are made black
+    if let Some(limit) = config.threshold.transparent_walls {
+        for (pixel, rgba) in grey.pixels_mut().zip(img.to_rgba8().pixels()) {
+            if rgba[3] < limit {
+                *pixel = Luma([0]);
+            }
+        }
+    }
+
+    if config.denoise > 0 {
+        grey = median(&grey, config.denoise);
+    }
+
+    let (rows, _) = img_to_vec_with_config(&DynamicImage::ImageLuma8(grey), &config.threshold)?;
+
+    let mut mask = Mask {
+        width: rows[0].len(),
+        height: rows.len(),
+        walls: rows.concat().iter().map(|&square| square == WALL).collect(),
+    };
+
+    if config.open > 0 {
+        mask = mask.erode(config.open).dilate(config.open);
+    }
+
+    if config.close > 0 {
+        mask = mask.dilate(config.close).erode(config.close);
+    }
+
+    if config.straighten || config.crop {
+        mask = mask.maze_walls();
+
+        let corners = mask.corners().ok_or(MazeError::EmptyMaze)?;
+
+        mask = if config.straighten {
+            mask.straighten(corners)
+        } else {
+            mask.crop()
+        };
+    }
+
+    Ok(mask.to_image())
+}
+
+// Preprocesses the image, then reads one square per wall and per corridor.
+//  How thick they are is the most common length of the runs of walls and
+//  of paths, so the odd smudge doesn't change it
+pub fn photo_to_maze(img: &DynamicImage, config: &PreprocessConfig) -> Result<Maze, MazeError> {
+    let clean = preprocess(img, config)?;
+
+    let mask = Mask {
+        width: clean.width() as usize,
+        height: clean.height() as usize,
+        walls: clean.pixels().map(|&Luma([luma])| luma < 128).collect(),
+    };
+
+    let (wall, corridor) = mask.thickness().ok_or(MazeError::EmptyMaze)?;
+
+    let lines = Axis::new(mask.height, wall, corridor);
+    let cols = Axis::new(mask.width, wall, corridor);
+
+    let mut cells = Vec::with_capacity(lines.squares * cols.squares);
+
+    for line in 0..lines.squares {
+        for col in 0..cols.squares {
+            let (x, y) = (cols.middle(col), lines.middle(line));
+            cells.push(if mask.is_wall(x, y) { WALL } else { PATH });
+        }
+    }
+
+    Maze::new(cols.squares, lines.squares, cells)
+}
+
+// The median of the square of `radius` around each pixel
+fn median(grey: &GrayImage, radius: u32) -> GrayImage {
+    let (width, height) = grey.dimensions();
+    let mut window = Vec::with_capacity(((2 * radius + 1) * (2 * radius + 1)) as usize);
+
+    GrayImage::from_fn(width, height, |x, y| {
+        window.clear();
+
+        for wy in y.saturating_sub(radius)..(y + radius + 1).min(height) {
+            for wx in x.saturating_sub(radius)..(x + radius + 1).min(width) {
+                window.push(grey.get_pixel(wx, wy)[0]);
+            }
+        }
+
+        let middle = window.len() / 2;
+        Luma([*window.select_nth_unstable(middle).1])
+    })
+}
+
+// Which pixels are walls, line by line
+#[derive(Debug, Clone)]
+struct Mask {
+    width: usize,
+    height: usize,
+    walls: Vec<bool>,
+}
+
+impl Mask {
+    fn is_wall(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.walls[y * self.width + x]
+    }
+
+    fn to_image(&self) -> GrayImage {
+        GrayImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            if self.is_wall(x as usize, y as usize) {
+                Luma([0])
+            } else {
+                Luma([255])
+            }
+        })
+    }
+
+    // ----------
+    // Morphology
+    // ----------
+    // A square structuring element, done as a line then a column
+    //  so it's 2 * radius + 1 pixels per pixel and not the square of it
+    fn dilate(&self, radius: u32) -> Mask {
+        self.spread(radius as usize, true)
+    }
+
+    fn erode(&self, radius: u32) -> Mask {
+        self.spread(radius as usize, false)
+    }
+
+    // Each pixel becomes `wall` if any pixel around it is
+    fn spread(&self, radius: usize, wall: bool) -> Mask {
+        let (width, height) = (self.width, self.height);
+        let mut lines = vec![!wall; self.walls.len()];
+        let mut walls = vec![!wall; self.walls.len()];
+
+        for y in 0..height {
+            for x in 0..width {
+                let from = x.saturating_sub(radius);
+                let to = (x + radius + 1).min(width);
+
+                lines[y * width + x] =
+                    self.walls[y * width + from..y * width + to].contains(&wall) == wall;
+            }
+        }
+
+        for y in 0..height {
+            for x in 0..width {
+                let from = y.saturating_sub(radius);
+                let to = (y + radius + 1).min(height);
+
+                walls[y * width + x] = (from..to).any(|wy| lines[wy * width + x] == wall) == wall;
+            }
+        }
+
+        Mask {
+            width,
+            height,
+            walls,
+        }
+    }
+
+    // The walls touching each other (diagonals too) that have the most pixels,
+    //  and the other groups in the rectangle around them. The openings of
+    //  the maze cut its outer wall in pieces, they're all in there
+    fn maze_walls(&self) -> Mask {
+        let mut component = vec![usize::MAX; self.walls.len()];
+        let mut queue = Queue::new();
+
+        // The first pixel of each group, how many pixels it has
+        //  and the rectangle around it as (left, top, right, bottom)
+        let mut groups: Vec<(usize, usize, [usize; 4])> = Vec::new();
+
+        for start in 0..self.walls.len() {
+            if !self.walls[start] || component[start] != usize::MAX {
+                continue;
+            }
+
+            let mut size = 0;
+            let mut bounds = [usize::MAX, usize::MAX, 0, 0];
+
+            component[start] = start;
+            queue.enqueue(start);
+
+            while let Some(i) = queue.dequeue() {
+                let (x, y) = (i % self.width, i / self.width);
+
+                size += 1;
+                bounds = [
+                    bounds[0].min(x),
+                    bounds[1].min(y),
+                    bounds[2].max(x),
+                    bounds[3].max(y),
+                ];
+
+                for ny in y.saturating_sub(1)..(y + 2).min(self.height) {
+                    for nx in x.saturating_sub(1)..(x + 2).min(self.width) {
+                        let next = ny * self.width + nx;
+
+                        if self.walls[next] && component[next] == usize::MAX {
+                            component[next] = start;
+                            queue.enqueue(next);
+                        }
+                    }
+                }
+            }
+
+            groups.push((start, size, bounds));
+        }
+
+        let [left, top, right, bottom] = match groups.iter().max_by_key(|group| group.1) {
+            Some(&(_, _, bounds)) => bounds,
+            None => return self.clone(),
+        };
+
+        // By the first pixel of the group
+        let mut kept = vec![false; self.walls.len()];
+
+        for &(start, _, [l, t, r, b]) in &groups {
+            kept[start] = l <= right && r >= left && t <= bottom && b >= top;
+        }
+
+        Mask {
+            width: self.width,
+            height: self.height,
+            walls: component
+                .iter()
+                .map(|&c| c != usize::MAX && kept[c])
+                .collect(),
+        }
+    }
+
+    // --------
+    // Geometry
+    // --------
+    // The walls closest to the corners of the image: top left, top right,
+    //  bottom right then bottom left, as (x, y)
+    fn corners(&self) -> Option<[(f64, f64); 4]> {
+        let walls: Vec<(f64, f64)> = (0..self.walls.len())
+            .filter(|&i| self.walls[i])
+            .map(|i| ((i % self.width) as f64, (i / self.width) as f64))
+            .collect();
+
+        let extreme = |score: fn(&(f64, f64)) -> f64| {
+            walls
+                .iter()
+                .copied()
+                .max_by(|a, b| score(a).total_cmp(&score(b)))
+        };
+
+        Some([
+            extreme(|&(x, y)| -(x + y))?,
+            extreme(|&(x, y)| x - y)?,
+            extreme(|&(x, y)| x + y)?,
+            extreme(|&(x, y)| y - x)?,
+        ])
+    }
+
+    fn crop(&self) -> Mask {
+        let walls = (0..self.walls.len()).filter(|&i| self.walls[i]);
+
+        let (mut left, mut top, mut right, mut bottom) = (usize::MAX, usize::MAX, 0, 0);
+
+        for i in walls {
+            let (x, y) = (i % self.width, i / self.width);
+
+            left = left.min(x);
+            right = right.max(x);
+            top = top.min(y);
+            bottom = bottom.max(y);
+        }
+
+        if left > right {
+            return self.clone();
+        }
+
+        let width = right - left + 1;
+
+        Mask {
+            width,
+            height: bottom - top + 1,
+            walls: (top..=bottom)
+                .flat_map(|y| (left..=right).map(move |x| (x, y)))
+                .map(|(x, y)| self.is_wall(x, y))
+                .collect(),
+        }
+    }
+
+    // The quadrilateral between the corners becomes a rectangle as wide as
+    //  its top and bottom sides and as high as its left and right sides
+    fn straighten(
+        &self,
+        [top_left, top_right, bottom_right, bottom_left]: [(f64, f64); 4],
+    ) -> Mask {
+        let distance = |(x1, y1): (f64, f64), (x2, y2): (f64, f64)| (x2 - x1).hypot(y2 - y1);
+
+        let width = ((distance(top_left, top_right) + distance(bottom_left, bottom_right)) / 2.0)
+            .round()
+            .max(1.0);
+        let height = ((distance(top_left, bottom_left) + distance(top_right, bottom_right)) / 2.0)
+            .round()
+            .max(1.0);
+
+        let (last_x, last_y) = (width, height);
+
+        let homography = match Homography::from_corners(
+            [(0.0, 0.0), (last_x, 0.0), (last_x, last_y), (0.0, last_y)],
+            [top_left, top_right, bottom_right, bottom_left],
+        ) {
+            Some(homography) => homography,
+            None => return self.crop(),
+        };
+
+        let (width, height) = (width as usize + 1, height as usize + 1);
+
+        Mask {
+            width,
+            height,
+            walls: (0..width * height)
+                .map(|i| {
+                    let (x, y) = homography.map(((i % width) as f64, (i / width) as f64));
+                    let (x, y) = (x.round(), y.round());
+
+                    x >= 0.0 && y >= 0.0 && self.is_wall(x as usize, y as usize)
+                })
+                .collect(),
+        }
+    }
+
+    // The most common length of the runs of walls and of paths,
+    //  along the lines and the columns
+    fn thickness(&self) -> Option<(usize, usize)> {
+        let mut walls = vec![0usize; self.width.max(self.height) + 1];
+        let mut paths = walls.clone();
+
+        let mut count = |squares: &mut dyn Iterator<Item = bool>| {
+            let mut runs: Vec<(bool, usize)> = Vec::new();
+
+            for wall in squares {
+                match runs.last_mut() {
+                    Some((last, length)) if *last == wall => *length += 1,
+                    _ => runs.push((wall, 1)),
+                }
+            }
+
+            for (wall, length) in runs {
+                if wall {
+                    walls[length] += 1;
+                } else {
+                    paths[length] += 1;
+                }
+            }
+        };
+
+        for y in 0..self.height {
+            count(&mut (0..self.width).map(|x| self.is_wall(x, y)));
+        }
+
+        for x in 0..self.width {
+            count(&mut (0..self.height).map(|y| self.is_wall(x, y)));
+        }
+
+        let mode = |runs: &[usize]| {
+            (1..runs.len())
+                .max_by_key(|&length| runs[length])
+                .filter(|&length| runs[length] > 0)
+        };
+
+        Some((mode(&walls)?, mode(&paths)?))
+    }
+}
+
+// Where the squares are along one side of a straightened maze: walls and
+//  corridors alternate, starting and ending with a wall. The pitch can be
+//  a fraction of a pixel, so the grid doesn't drift on big photos
+struct Axis {
+    squares: usize,
+    pitch: f64,
+    wall: f64,
+}
+
+impl Axis {
+    fn new(size: usize, wall: usize, corridor: usize) -> Self {
+        let guess = (wall + corridor) as f64;
+        let cells = ((size.saturating_sub(wall)) as f64 / guess)
+            .round()
+            .max(1.0);
+        let pitch = size.saturating_sub(wall) as f64 / cells;
+
+        Axis {
+            squares: 2 * cells as usize + 1,
+            pitch,
+            wall: wall as f64 * pitch / guess,
+        }
+    }
+
+    // The pixel in the middle of a square
+    fn middle(&self, square: usize) -> usize {
+        let start = (square / 2) as f64 * self.pitch;
+
+        let middle = if square % 2 == 0 {
+            start + self.wall / 2.0
+        } else {
+            start + self.wall + (self.pitch - self.wall) / 2.0
+        };
+
+        middle as usize
+    }
+}
+
+// Maps points of one plane to another, e.g. a straight maze to its photo
+struct Homography([f64; 8]);
+
+impl Homography {
+    // Sends each of `from` to the same corner of `to`,
+    //  None if three of the corners are on a line
+    fn from_corners(from: [(f64, f64); 4], to: [(f64, f64); 4]) -> Option<Self> {
+        // x' = (a x + b y + c) / (g x + h y + 1), y' = (d x + e y + f) / (g x + h y + 1)
+        let mut system = [[0.0; 9]; 8];
+
+        for (i, (&(x, y), &(u, v))) in from.iter().zip(&to).enumerate() {
+            system[2 * i] = [x, y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y, u];
+            system[2 * i + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -v * x, -v * y, v];
+        }
+
+        // Gaussian elimination, with the biggest pivot for stability
+        for col in 0..8 {
+            let pivot =
+                (col..8).max_by(|&a, &b| system[a][col].abs().total_cmp(&system[b][col].abs()))?;
+
+            if system[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+
+            system.swap(col, pivot);
+
+            let pivot = system[col];
+
+            for (row, equation) in system.iter_mut().enumerate() {
+                if row != col {
+                    let factor = equation[col] / pivot[col];
+
+                    for (value, pivot_value) in equation.iter_mut().zip(&pivot).skip(col) {
+                        *value -= factor * pivot_value;
+                    }
+                }
+            }
+        }
+
+        let mut coefficients = [0.0; 8];
+
+        for (i, coefficient) in coefficients.iter_mut().enumerate() {
+            *coefficient = system[i][8] / system[i][i];
+        }
+
+        Some(Homography(coefficients))
+    }
+
+    fn map(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let [a, b, c, d, e, f, g, h] = self.0;
+        let w = g * x + h * y + 1.0;
+
+        ((a * x + b * y + c) / w, (d * x + e * y + f) / w)
+    }
+}
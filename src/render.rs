@@ -0,0 +1,205 @@
+use image::codecs::pnm::{PnmSubtype, SampleEncoding};
+use image::{DynamicImage, ImageOutputFormat, Rgb, RgbImage};
+
+use crate::{gradient_color, Maze, MazeError, Pos};
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Png,
+    // With a quality from 1 to 100
+    Jpeg(u8),
+    Bmp,
+    // Lossless
+    WebP,
+    // Binary PPM
+    Ppm,
+}
+
+impl OutputFormat {
+    // png, jpg/jpeg, bmp, webp, ppm
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "png" => Some(OutputFormat::Png),
+            "jpg" | "jpeg" => Some(OutputFormat::Jpeg(90)),
+            "bmp" => Some(OutputFormat::Bmp),
+            "webp" => Some(OutputFormat::WebP),
+            "ppm" => Some(OutputFormat::Ppm),
+            _ => None,
+        }
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        Self::from_name(path.as_ref().extension()?.to_str()?)
+    }
+}
+
+// How the solution is drawn over the path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolutionColor {
+    Solid(Rgb<u8>),
+    // From the start to the end, evenly spaced, see gradient_color
+    Gradient(Vec<Rgb<u8>>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderStyle {
+    pub wall: Rgb<u8>,
+    pub path: Rgb<u8>,
+    pub explored: Rgb<u8>,
+    pub solution: SolutionColor,
+    pub start: Rgb<u8>,
+    pub end: Rgb<u8>,
+    // Pixels per square
+    pub scale: u32,
+    // Width of the solution line in pixels, the whole square if it's
+    //  as big as `scale` or 0
+    pub thickness: u32,
+    // None to go by the extension of the output
+    pub format: Option<OutputFormat>,
+}
+
+impl Default for RenderStyle {
+    fn default() -> Self {
+        Self {
+            wall: Rgb([0, 0, 0]),
+            path: Rgb([255, 255, 255]),
+            explored: Rgb([150, 190, 255]),
+            solution: SolutionColor::Solid(Rgb([255, 0, 0])),
+            start: Rgb([0, 200, 0]),
+            end: Rgb([0, 0, 255]),
+            scale: 1,
+            thickness: 0,
+            format: None,
+        }
+    }
+}
+
+// The explored squares first, then the solution, then the start and the end
+pub fn render_solution(
+    maze: &Maze,
+    solution: &[Pos],
+    explored: &[Pos],
+    style: &RenderStyle,
+) -> RgbImage {
+    let scale = style.scale.max(1);
+    let thickness = match style.thickness {
+        0 => scale,
+        thickness => thickness.min(scale),
+    };
+
+    let mut img = RgbImage::from_fn(
+        maze.width() as u32 * scale,
+        maze.height() as u32 * scale,
+        |x, y| {
+            if maze.is_open(((y / scale) as usize, (x / scale) as usize)) {
+                style.path
+            } else {
+                style.wall
+            }
+        },
+    );
+
+    // Pixels from `from` to `to` included, both as (x, y)
+    let mut fill = |(x0, y0): (u32, u32), (x1, y1): (u32, u32), color: Rgb<u8>| {
+        for y in y0.min(y1)..=y0.max(y1) {
+            for x in x0.min(x1)..=x0.max(x1) {
+                img.put_pixel(x, y, color);
+            }
+        }
+    };
+
+    let square = |(line, col): Pos| {
+        let (x, y) = (col as u32 * scale, line as u32 * scale);
+        ((x, y), (x + scale - 1, y + scale - 1))
+    };
+
+    // The line goes through the middle of the squares
+    let middle = |(line, col): Pos| {
+        let offset = (scale - thickness) / 2;
+        let (x, y) = (col as u32 * scale + offset, line as u32 * scale + offset);
+        ((x, y), (x + thickness - 1, y + thickness - 1))
+    };
+
+    for &pos in explored.iter().filter(|&&pos| maze.contains(pos)) {
+        let (from, to) = square(pos);
+        fill(from, to, style.explored);
+    }
+
+    let solution: Vec<Pos> = solution
+        .iter()
+        .copied()
+        .filter(|&pos| maze.contains(pos))
+        .collect();
+
+    for (i, &pos) in solution.iter().enumerate() {
+        let color = match &style.solution {
+            SolutionColor::Solid(color) => *color,
+            SolutionColor::Gradient(gradient) => gradient_color(
+                gradient,
+                i as f64 / solution.len().saturating_sub(1).max(1) as f64,
+            ),
+        };
+
+        let (from, to) = middle(pos);
+        fill(from, to, color);
+
+        // Joined to the previous square, so it's a line and not dots
+        if let Some(&previous) = i.checked_sub(1).and_then(|i| solution.get(i)) {
+            let (previous, _) = middle(previous);
+            fill(previous, to, color);
+        }
+    }
+
+    for (marker, color) in [(maze.start(), style.start), (maze.end(), style.end)] {
+        if let Ok(pos) = marker {
+            let (from, to) = square(pos);
+            fill(from, to, color);
+        }
+    }
+
+    img
+}
+
+// Renders the maze and its solution, then saves it
+//  in the format of the style or of the extension
+pub fn output_maze_solution_with_style(
+    maze: &Maze,
+    solution: &[Pos],
+    explored: &[Pos],
+    style: &RenderStyle,
+    output: &str,
+) -> Result<(), MazeError> {
+    let img = DynamicImage::ImageRgb8(render_solution(maze, solution, explored, style));
+
+    save_image(&img, style.format, output)
+}
+
+// Saves the image in `format`, or in the format of the extension when it's None
+pub fn save_image(
+    img: &DynamicImage,
+    format: Option<OutputFormat>,
+    output: &str,
+) -> Result<(), MazeError> {
+    let format = match format.or_else(|| OutputFormat::from_path(output)) {
+        Some(format) => format,
+        // Something the image crate knows, like tiff
+        None => return img.save(output).map_err(MazeError::ImageEncode),
+    };
+
+    let format = match format {
+        OutputFormat::Png => ImageOutputFormat::Png,
+        OutputFormat::Jpeg(quality) => ImageOutputFormat::Jpeg(quality.clamp(1, 100)),
+        OutputFormat::Bmp => ImageOutputFormat::Bmp,
+        OutputFormat::Ppm => ImageOutputFormat::Pnm(PnmSubtype::Pixmap(SampleEncoding::Binary)),
+        OutputFormat::WebP => ImageOutputFormat::WebP,
+    };
+
+    let file = File::create(output).map_err(MazeError::Io)?;
+
+    img.write_to(&mut BufWriter::new(file), format)
+        .map_err(MazeError::ImageEncode)
+}
@@ -0,0 +1,248 @@
+use crate::algorithms::{
+    a_star, backtracking_breadth_first, bidirectional_breadth_first, breadth_first, depth_first,
+    dijkstra, graph_dijkstra, simple_always_left, wall_follower, Heuristic,
+};
+use crate::graph::{Graph, JunctionGraph};
+use crate::Maze;
+use crate::MazeError;
+use crate::Pos;
+
+use std::time::Duration;
+
+// What an algorithm found, the path and the exploration order are kept apart
+//  so they can be rendered or scored on their own
+#[derive(Debug, Clone, Default)]
+pub struct SolveResult {
+    // From the start to the end, every square is next to the previous one.
+    //  None if the algorithm didn't reach the end
+    pub path: Option<Vec<Pos>>,
+    // Every square in the order the algorithm visited it
+    pub explored: Vec<Pos>,
+    // Sum of the costs of the squares of the path, see Maze::path_cost
+    pub cost: Option<u64>,
+    pub stats: SolveStats,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SolveStats {
+    // How many squares had their neighbours looked at
+    pub nodes_expanded: usize,
+    // The biggest the stack/queue got while solving
+    pub max_frontier: usize,
+    pub elapsed: Duration,
+}
+
+// Every algorithm can be used through this trait,
+//  so callers don't have to care about what each one needs
+pub trait Solver: Send + Sync {
+    // Unique name, used to look the solver up in a registry
+    fn name(&self) -> &'static str;
+
+    fn solve(&self, maze: &Maze) -> Result<SolveResult, MazeError>;
+}
+
+pub struct AlwaysLeft;
+pub struct WallFollower;
+pub struct DepthFirst;
+pub struct BreadthFirst;
+pub struct BacktrackingBreadthFirst;
+pub struct BidirectionalBreadthFirst;
+pub struct AStar(pub Heuristic);
+pub struct Dijkstra;
+// A graph solver run on the junctions only, see JunctionGraph
+pub struct Junctions(pub GraphSearch);
+
+// The solvers that work on a Graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphSearch {
+    DepthFirst,
+    // Fewest junctions, not the shortest path
+    BreadthFirst,
+    BacktrackingBreadthFirst,
+    Dijkstra,
+}
+
+impl GraphSearch {
+    pub fn search(self, maze: &Maze, graph: &Graph) -> Result<SolveResult, MazeError> {
+        match self {
+            GraphSearch::DepthFirst => depth_first(maze, graph),
+            GraphSearch::BreadthFirst => breadth_first(maze, graph),
+            GraphSearch::BacktrackingBreadthFirst => backtracking_breadth_first(maze, graph),
+            GraphSearch::Dijkstra => graph_dijkstra(maze, graph),
+        }
+    }
+}
+
+impl Solver for AlwaysLeft {
+    fn name(&self) -> &'static str {
+        "always_left"
+    }
+
+    fn solve(&self, maze: &Maze) -> Result<SolveResult, MazeError> {
+        simple_always_left(maze)
+    }
+}
+
+impl Solver for WallFollower {
+    fn name(&self) -> &'static str {
+        "wall_follower"
+    }
+
+    fn solve(&self, maze: &Maze) -> Result<SolveResult, MazeError> {
+        wall_follower(maze)
+    }
+}
+
+impl Solver for DepthFirst {
+    fn name(&self) -> &'static str {
+        "depth_first"
+    }
+
+    fn solve(&self, maze: &Maze) -> Result<SolveResult, MazeError> {
+        depth_first(maze, &Graph::from_maze(maze))
+    }
+}
+
+impl Solver for BreadthFirst {
+    fn name(&self) -> &'static str {
+        "breadth_first"
+    }
+
+    fn solve(&self, maze: &Maze) -> Result<SolveResult, MazeError> {
+        breadth_first(maze, &Graph::from_maze(maze))
+    }
+}
+
+impl Solver for BacktrackingBreadthFirst {
+    fn name(&self) -> &'static str {
+        "backtracking_breadth_first"
+    }
+
+    fn solve(&self, maze: &Maze) -> Result<SolveResult, MazeError> {
+        backtracking_breadth_first(maze, &Graph::from_maze(maze))
+    }
+}
+
+impl Solver for BidirectionalBreadthFirst {
+    fn name(&self) -> &'static str {
+        "bidirectional_breadth_first"
+    }
+
+    fn solve(&self, maze: &Maze) -> Result<SolveResult, MazeError> {
+        bidirectional_breadth_first(maze).map(|(result, _)| result)
+    }
+}
+
+impl Solver for AStar {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            Heuristic::Manhattan => "a_star_manhattan",
+            Heuristic::Euclidean => "a_star_euclidean",
+            Heuristic::Chebyshev => "a_star_chebyshev",
+            Heuristic::Zero => "a_star_zero",
+        }
+    }
+
+    fn solve(&self, maze: &Maze) -> Result<SolveResult, MazeError> {
+        a_star(maze, self.0)
+    }
+}
+
+impl Solver for Dijkstra {
+    fn name(&self) -> &'static str {
+        "dijkstra"
+    }
+
+    fn solve(&self, maze: &Maze) -> Result<SolveResult, MazeError> {
+        dijkstra(maze)
+    }
+}
+
+impl Solver for Junctions {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            GraphSearch::DepthFirst => "junction_depth_first",
+            GraphSearch::BreadthFirst => "junction_breadth_first",
+            GraphSearch::BacktrackingBreadthFirst => "junction_backtracking_breadth_first",
+            GraphSearch::Dijkstra => "junction_dijkstra",
+        }
+    }
+
+    fn solve(&self, maze: &Maze) -> Result<SolveResult, MazeError> {
+        let search = self.0;
+
+        JunctionGraph::new(maze).solve(maze, |maze, graph| search.search(maze, graph))
+    }
+}
+
+// --------
+// Registry
+// --------
+#[derive(Default)]
+pub struct Registry {
+    solvers: Vec<Box<dyn Solver>>,
+}
+
+impl Registry {
+    // An empty registry
+    pub fn new() -> Self {
+        Self {
+            solvers: Vec::new(),
+        }
+    }
+
+    // A registry with every algorithm of the crate
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::new();
+
+        registry.register(Box::new(AlwaysLeft));
+        registry.register(Box::new(WallFollower));
+        registry.register(Box::new(DepthFirst));
+        registry.register(Box::new(BreadthFirst));
+        registry.register(Box::new(BacktrackingBreadthFirst));
+        registry.register(Box::new(BidirectionalBreadthFirst));
+        registry.register(Box::new(AStar(Heuristic::Manhattan)));
+        registry.register(Box::new(AStar(Heuristic::Euclidean)));
+        registry.register(Box::new(AStar(Heuristic::Chebyshev)));
+        registry.register(Box::new(AStar(Heuristic::Zero)));
+        registry.register(Box::new(Dijkstra));
+        registry.register(Box::new(Junctions(GraphSearch::DepthFirst)));
+        registry.register(Box::new(Junctions(GraphSearch::BreadthFirst)));
+        registry.register(Box::new(Junctions(GraphSearch::BacktrackingBreadthFirst)));
+        registry.register(Box::new(Junctions(GraphSearch::Dijkstra)));
+
+        registry
+    }
+
+    // Adds a solver, replacing the one that already has the same name
+    pub fn register(&mut self, solver: Box<dyn Solver>) {
+        match self.solvers.iter().position(|s| s.name() == solver.name()) {
+            Some(i) => self.solvers[i] = solver,
+            None => self.solvers.push(solver),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Solver> {
+        self.solvers
+            .iter()
+            .find(|s| s.name() == name)
+            .map(|s| s.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.solvers.iter().map(|s| s.name()).collect()
+    }
+
+    // In the order they were registered
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Solver> {
+        self.solvers.iter().map(|s| s.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.solvers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.solvers.is_empty()
+    }
+}
@@ -0,0 +1,206 @@
+use image::Rgb;
+
+use crate::{Maze, MazeError, Pos};
+
+use std::collections::HashMap;
+use std::fs;
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+// What the SVG looks like, sizes are in SVG user units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgStyle {
+    // Width and height of a square
+    pub cell_size: f64,
+    pub wall: Rgb<u8>,
+    // Outline of the walls, 0 for none
+    pub wall_stroke: f64,
+    pub background: Rgb<u8>,
+    pub path: Rgb<u8>,
+    pub path_width: f64,
+    // The explored squares are drawn under the path when there's a color
+    pub explored: Option<Rgb<u8>>,
+    pub explored_opacity: f64,
+}
+
+impl Default for SvgStyle {
+    fn default() -> Self {
+        Self {
+            cell_size: 10.0,
+            wall: Rgb([0, 0, 0]),
+            wall_stroke: 0.0,
+            background: Rgb([255, 255, 255]),
+            path: Rgb([255, 0, 0]),
+            path_width: 4.0,
+            explored: None,
+            explored_opacity: 0.5,
+        }
+    }
+}
+
+// A rectangle of squares: line, column, width and height
+type Rect = (usize, usize, usize, usize);
+
+// The background, the walls, the explored squares then the solution,
+//  each in its own group so they can be edited apart
+pub fn maze_to_svg(maze: &Maze, solution: &[Pos], explored: &[Pos], style: &SvgStyle) -> String {
+    let size = style.cell_size;
+    let (width, height) = (maze.width() as f64 * size, maze.height() as f64 * size);
+
+    // One element per line
+    let mut svg = Vec::new();
+
+    svg.push(format!(
+        r#"<svg xmlns="{}" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        SVG_NAMESPACE,
+        w = width,
+        h = height
+    ));
+    svg.push(format!(
+        r#"  <rect id="background" width="{}" height="{}" fill="{}"/>"#,
+        width,
+        height,
+        hex(style.background)
+    ));
+
+    let stroke = if style.wall_stroke > 0.0 {
+        format!(
+            r#" stroke="{}" stroke-width="{}""#,
+            hex(style.wall),
+            style.wall_stroke
+        )
+    } else {
+        String::new()
+    };
+
+    let walls: Vec<Pos> = (0..maze.len())
+        .map(|i| maze.pos(i))
+        .filter(|&pos| !maze.is_open(pos))
+        .collect();
+
+    svg.push(format!(
+        r#"  <g id="walls" fill="{}"{}>"#,
+        hex(style.wall),
+        stroke
+    ));
+    write_rects(&mut svg, &merge_rects(maze, &walls), size);
+    svg.push(String::from("  </g>"));
+
+    if let Some(color) = style.explored {
+        svg.push(format!(
+            r#"  <g id="explored" fill="{}" fill-opacity="{}">"#,
+            hex(color),
+            style.explored_opacity
+        ));
+        write_rects(&mut svg, &merge_rects(maze, explored), size);
+        svg.push(String::from("  </g>"));
+    }
+
+    if !solution.is_empty() {
+        let points: Vec<String> = solution
+            .iter()
+            .map(|&(line, col)| {
+                format!(
+                    "{},{}",
+                    (col as f64 + 0.5) * size,
+                    (line as f64 + 0.5) * size
+                )
+            })
+            .collect();
+
+        // Rounded joins, so the corners of the path aren't sharp
+        svg.push(format!(
+            concat!(
+                r#"  <polyline id="solution" points="{}" fill="none""#,
+                r#" stroke="{}" stroke-width="{}""#,
+                r#" stroke-linejoin="round" stroke-linecap="round"/>"#
+            ),
+            points.join(" "),
+            hex(style.path),
+            style.path_width
+        ));
+    }
+
+    svg.push(String::from("</svg>\n"));
+
+    svg.join("\n")
+}
+
+pub fn save_svg(
+    maze: &Maze,
+    solution: &[Pos],
+    explored: &[Pos],
+    style: &SvgStyle,
+    output: &str,
+) -> Result<(), MazeError> {
+    fs::write(output, maze_to_svg(maze, solution, explored, style)).map_err(MazeError::Io)
+}
+
+// Squares next to each other on a line become one rectangle,
+//  then the same rectangles on the following lines are merged
+fn merge_rects(maze: &Maze, squares: &[Pos]) -> Vec<Rect> {
+    let mut marked = vec![false; maze.len()];
+
+    for &square in squares {
+        if maze.contains(square) {
+            marked[maze.index(square)] = true;
+        }
+    }
+
+    let mut rects: Vec<Rect> = Vec::new();
+    // (start column, width) of the rectangles that reached the previous line
+    let mut open: HashMap<(usize, usize), usize> = HashMap::new();
+
+    for (line, cols) in marked.chunks(maze.width()).enumerate() {
+        let mut reached = HashMap::new();
+        let mut col = 0;
+
+        while col < cols.len() {
+            if !cols[col] {
+                col += 1;
+                continue;
+            }
+
+            let start = col;
+
+            while col < cols.len() && cols[col] {
+                col += 1;
+            }
+
+            let run = (start, col - start);
+
+            let i = match open.get(&run) {
+                Some(&i) => {
+                    rects[i].3 += 1;
+                    i
+                }
+                None => {
+                    rects.push((line, start, run.1, 1));
+                    rects.len() - 1
+                }
+            };
+
+            reached.insert(run, i);
+        }
+
+        open = reached;
+    }
+
+    rects
+}
+
+fn write_rects(svg: &mut Vec<String>, rects: &[Rect], size: f64) {
+    for &(line, col, width, height) in rects {
+        svg.push(format!(
+            r#"    <rect x="{}" y="{}" width="{}" height="{}"/>"#,
+            col as f64 * size,
+            line as f64 * size,
+            width as f64 * size,
+            height as f64 * size
+        ));
+    }
+}
+
+fn hex(Rgb([r, g, b]): Rgb<u8>) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
@@ -0,0 +1,313 @@
+use image::Rgb;
+use terminal_size::{terminal_size, Width};
+
+use crate::constants::PATH;
+use crate::{Maze, Pos};
+
+use std::env;
+use std::io::{self, Write};
+
+// Used when the width of the terminal isn't known
+pub const DEFAULT_COLUMNS: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalMode {
+    // Two squares per character, one on top of the other
+    HalfBlocks,
+    // One square per character, walls are joined with box-drawing lines
+    BoxDrawing,
+}
+
+// What to do with a maze wider than the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    // Squares are grouped so the maze fits
+    Downsample,
+    // The maze is cut into strips as wide as the terminal, one after the other
+    Page,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalStyle {
+    pub mode: TerminalMode,
+    pub overflow: Overflow,
+    // None to look at the terminal, see terminal_columns
+    pub columns: Option<usize>,
+    pub wall: Rgb<u8>,
+    pub open: Rgb<u8>,
+    pub explored: Rgb<u8>,
+    pub solution: Rgb<u8>,
+    pub start: Rgb<u8>,
+    pub end: Rgb<u8>,
+}
+
+impl Default for TerminalStyle {
+    fn default() -> Self {
+        Self {
+            mode: TerminalMode::HalfBlocks,
+            overflow: Overflow::Downsample,
+            columns: None,
+            wall: Rgb([40, 40, 40]),
+            open: Rgb([230, 230, 230]),
+            explored: Rgb([150, 190, 255]),
+            solution: Rgb([255, 0, 0]),
+            start: Rgb([0, 200, 0]),
+            end: Rgb([0, 0, 255]),
+        }
+    }
+}
+
+// What a character shows, when squares are grouped the later kinds win
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Wall,
+    Open,
+    Explored,
+    Solution,
+    Start,
+    End,
+}
+
+// The width of the terminal stdout is on. When it isn't one (e.g. piped)
+//  $COLUMNS is tried, shells set it but don't always export it
+pub fn terminal_columns() -> usize {
+    if let Some((Width(columns), _)) = terminal_size() {
+        if columns > 0 {
+            return usize::from(columns);
+        }
+    }
+
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|&columns| columns > 0)
+        .unwrap_or(DEFAULT_COLUMNS)
+}
+
+// Draws the maze with 24-bit colors, the explored squares under the solution
+pub fn render<W: Write>(
+    out: &mut W,
+    maze: &Maze,
+    solution: &[Pos],
+    explored: &[Pos],
+    style: &TerminalStyle,
+) -> io::Result<()> {
+    let mut kinds: Vec<Kind> = maze
+        .cells()
+        .iter()
+        .map(|&square| {
+            if square == PATH {
+                Kind::Open
+            } else {
+                Kind::Wall
+            }
+        })
+        .collect();
+
+    let mut mark = |squares: &[Pos], kind: Kind| {
+        for &square in squares.iter().filter(|&&square| maze.contains(square)) {
+            kinds[maze.index(square)] = kind;
+        }
+    };
+
+    mark(explored, Kind::Explored);
+    mark(solution, Kind::Solution);
+
+    if let Ok(start) = maze.start() {
+        mark(&[start], Kind::Start);
+    }
+
+    if let Ok(end) = maze.end() {
+        mark(&[end], Kind::End);
+    }
+
+    let columns = style.columns.unwrap_or_else(terminal_columns).max(1);
+    let grid = Grid {
+        kinds,
+        width: maze.width(),
+        height: maze.height(),
+    };
+
+    match style.overflow {
+        Overflow::Downsample => {
+            // Groups of factor x factor squares, so the maze keeps its shape
+            let factor = grid.width.div_ceil(columns).max(1);
+
+            grid.downsample(factor)
+                .write(out, style, 0, grid.width.div_ceil(factor))
+        }
+        Overflow::Page => {
+            let mut left = 0;
+
+            while left < grid.width {
+                let right = (left + columns).min(grid.width);
+
+                if left > 0 {
+                    writeln!(out)?;
+                }
+
+                grid.write(out, style, left, right)?;
+                left = right;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+struct Grid {
+    kinds: Vec<Kind>,
+    width: usize,
+    height: usize,
+}
+
+impl Grid {
+    fn get(&self, line: usize, col: usize) -> Kind {
+        self.kinds[line * self.width + col]
+    }
+
+    fn is_wall(&self, line: usize, col: usize) -> bool {
+        line < self.height && col < self.width && self.get(line, col) == Kind::Wall
+    }
+
+    // A marked square shows through, otherwise the most common
+    //  of wall and open wins
+    fn downsample(&self, factor: usize) -> Grid {
+        if factor == 1 {
+            return Grid {
+                kinds: self.kinds.clone(),
+                width: self.width,
+                height: self.height,
+            };
+        }
+
+        let (width, height) = (self.width.div_ceil(factor), self.height.div_ceil(factor));
+        let mut kinds = Vec::with_capacity(width * height);
+
+        for line in 0..height {
+            for col in 0..width {
+                let lines = line * factor..((line + 1) * factor).min(self.height);
+                let cols = col * factor..((col + 1) * factor).min(self.width);
+
+                let group: Vec<Kind> = lines
+                    .flat_map(|l| cols.clone().map(move |c| (l, c)))
+                    .map(|(l, c)| self.get(l, c))
+                    .collect();
+
+                let walls = group.iter().filter(|&&kind| kind == Kind::Wall).count();
+                let top = group.iter().copied().max().unwrap_or(Kind::Wall);
+
+                kinds.push(if top > Kind::Explored || walls * 2 <= group.len() {
+                    top
+                } else {
+                    Kind::Wall
+                });
+            }
+        }
+
+        Grid {
+            kinds,
+            width,
+            height,
+        }
+    }
+
+    // Columns `left..right`
+    fn write<W: Write>(
+        &self,
+        out: &mut W,
+        style: &TerminalStyle,
+        left: usize,
+        right: usize,
+    ) -> io::Result<()> {
+        match style.mode {
+            TerminalMode::HalfBlocks => {
+                for line in (0..self.height).step_by(2) {
+                    for col in left..right {
+                        let top = color(style, self.get(line, col));
+
+                        if line + 1 < self.height {
+                            let bottom = color(style, self.get(line + 1, col));
+                            write!(out, "{}{}\u{2580}", fg(top), bg(bottom))?;
+                        } else {
+                            write!(out, "{}{}\u{2580}", RESET, fg(top))?;
+                        }
+                    }
+
+                    writeln!(out, "{}", RESET)?;
+                }
+            }
+            TerminalMode::BoxDrawing => {
+                for line in 0..self.height {
+                    for col in left..right {
+                        let kind = self.get(line, col);
+
+                        if kind == Kind::Wall {
+                            write!(
+                                out,
+                                "{}{}{}",
+                                bg(style.open),
+                                fg(style.wall),
+                                self.wall_char(line, col)
+                            )?;
+                        } else {
+                            write!(out, "{} ", bg(color(style, kind)))?;
+                        }
+                    }
+
+                    writeln!(out, "{}", RESET)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // The line joins the walls above, below, on the left and on the right
+    fn wall_char(&self, line: usize, col: usize) -> char {
+        let up = line > 0 && self.is_wall(line - 1, col);
+        let down = self.is_wall(line + 1, col);
+        let left = col > 0 && self.is_wall(line, col - 1);
+        let right = self.is_wall(line, col + 1);
+
+        match (up, down, left, right) {
+            (false, false, false, false) => '\u{25a0}',
+            (true, false, false, false) => '\u{2575}',
+            (false, true, false, false) => '\u{2577}',
+            (false, false, true, false) => '\u{2574}',
+            (false, false, false, true) => '\u{2576}',
+            (true, true, false, false) => '\u{2502}',
+            (false, false, true, true) => '\u{2500}',
+            (false, true, false, true) => '\u{250c}',
+            (false, true, true, false) => '\u{2510}',
+            (true, false, false, true) => '\u{2514}',
+            (true, false, true, false) => '\u{2518}',
+            (true, true, false, true) => '\u{251c}',
+            (true, true, true, false) => '\u{2524}',
+            (false, true, true, true) => '\u{252c}',
+            (true, false, true, true) => '\u{2534}',
+            (true, true, true, true) => '\u{253c}',
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+fn color(style: &TerminalStyle, kind: Kind) -> Rgb<u8> {
+    match kind {
+        Kind::Wall => style.wall,
+        Kind::Open => style.open,
+        Kind::Explored => style.explored,
+        Kind::Solution => style.solution,
+        Kind::Start => style.start,
+        Kind::End => style.end,
+    }
+}
+
+fn fg(Rgb([r, g, b]): Rgb<u8>) -> String {
+    format!("\x1b[38;2;{};{};{}m", r, g, b)
+}
+
+fn bg(Rgb([r, g, b]): Rgb<u8>) -> String {
+    format!("\x1b[48;2;{};{};{}m", r, g, b)
+}
@@ -0,0 +1,376 @@
+use crate::constants::{PATH, WALL};
+use crate::{Maze, MazeError, Pos};
+
+use std::fs;
+
+// The characters of a text maze. When reading, any of `walls` is a wall and
+//  any of `paths` a path, the first ones are written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Charset {
+    pub walls: Vec<char>,
+    pub paths: Vec<char>,
+    pub start: char,
+    pub end: char,
+    // Written on the squares of the solution, read as a path
+    pub solution: char,
+}
+
+impl Default for Charset {
+    fn default() -> Self {
+        Self {
+            walls: vec!['#'],
+            paths: vec!['.', ' '],
+            start: 'S',
+            end: 'E',
+            solution: '*',
+        }
+    }
+}
+
+// One character per square, line by line. Without a start or an end marker
+//  they are looked for on the borders, like in an image
+pub fn text_to_maze(text: &str, charset: &Charset) -> Result<Maze, MazeError> {
+    let lines = text_lines(text)?;
+    let width = lines[0].len();
+
+    let mut cells = Vec::with_capacity(width * lines.len());
+    let mut start = None;
+    let mut end = None;
+
+    for (line, chars) in lines.iter().enumerate() {
+        if chars.len() != width {
+            return Err(parse_error(
+                (line, chars.len().min(width)),
+                format!("{} characters, expected {}", chars.len(), width),
+            ));
+        }
+
+        for (col, &c) in chars.iter().enumerate() {
+            let square = if c == charset.start {
+                set_marker(&mut start, (line, col), (line, col), "start")?;
+                PATH
+            } else if c == charset.end {
+                set_marker(&mut end, (line, col), (line, col), "end")?;
+                PATH
+            } else if charset.walls.contains(&c) {
+                WALL
+            } else if charset.paths.contains(&c) || c == charset.solution {
+                PATH
+            } else {
+                return Err(parse_error(
+                    (line, col),
+                    format!("unknown character {:?}", c),
+                ));
+            };
+
+            cells.push(square);
+        }
+    }
+
+    with_markers(Maze::new(width, lines.len(), cells)?, start, end)
+}
+
+// One character per square, the start and the end get their markers
+//  and the solution is drawn over the paths
+pub fn maze_to_text(maze: &Maze, solution: &[Pos], charset: &Charset) -> String {
+    let wall = charset.walls.first().copied().unwrap_or('#');
+    let path = charset.paths.first().copied().unwrap_or(' ');
+
+    let mut chars: Vec<char> = maze
+        .cells()
+        .iter()
+        .map(|&square| if square == WALL { wall } else { path })
+        .collect();
+
+    for &square in solution.iter().filter(|&&square| maze.contains(square)) {
+        chars[maze.index(square)] = charset.solution;
+    }
+
+    if let Ok(start) = maze.start() {
+        chars[maze.index(start)] = charset.start;
+    }
+
+    if let Ok(end) = maze.end() {
+        chars[maze.index(end)] = charset.end;
+    }
+
+    lines_to_string(chars.chunks(maze.width()))
+}
+
+// -------------
+// Classic style
+// -------------
+// +--+--+
+// |  |  |
+// +  +--+
+// Corners are `+`, walls between cells are `--` and `|`, missing walls
+//  are spaces. Every cell becomes an open square with a square between
+//  it and its neighbours, like the generators do. `S` and `E` mark
+//  the start and the end
+pub fn classic_to_maze(text: &str) -> Result<Maze, MazeError> {
+    let mut lines = text_lines(text)?;
+
+    if lines[0][0] != '+' {
+        return Err(parse_error((0, 0), String::from("expected '+'")));
+    }
+
+    // The number of characters between two corners
+    let cell_width = match lines[0].iter().skip(1).position(|&c| c == '+') {
+        Some(0) | None => {
+            return Err(parse_error(
+                (0, 1),
+                String::from("expected a cell after the corner"),
+            ))
+        }
+        Some(cell_width) => cell_width,
+    };
+
+    let pitch = cell_width + 1;
+
+    if (lines[0].len() - 1) % pitch != 0 {
+        return Err(parse_error(
+            (0, lines[0].len() - 1),
+            format!(
+                "the line doesn't end on a corner, cells are {} wide",
+                cell_width
+            ),
+        ));
+    }
+
+    if lines.len() % 2 == 0 {
+        return Err(parse_error(
+            (lines.len() - 1, 0),
+            String::from("expected a line of corners to close the maze"),
+        ));
+    }
+
+    let length = lines[0].len();
+    let width = 2 * ((length - 1) / pitch) + 1;
+    let height = lines.len();
+
+    let mut cells = vec![WALL; width * height];
+    let mut start = None;
+    let mut end = None;
+
+    for (line, chars) in lines.iter_mut().enumerate() {
+        if chars.len() > length {
+            return Err(parse_error(
+                (line, length),
+                format!("{} characters, expected {}", chars.len(), length),
+            ));
+        }
+
+        // Editors often drop the spaces of an opening on the right
+        chars.resize(length, ' ');
+
+        for col in 0..width {
+            // Where the square starts in the text, and how wide it is
+            let at = (col / 2) * pitch + col % 2;
+            let part = if col % 2 == 0 {
+                &chars[at..at + 1]
+            } else {
+                &chars[at..at + cell_width]
+            };
+
+            let square = (line, col);
+            let open = open_part(part, square, (line, at), &mut start, &mut end)?;
+
+            cells[line * width + col] = match (line % 2 == 0, col % 2 == 0) {
+                // Corner
+                (true, true) => expect_all(part, '+', (line, at))?,
+                (_, _) if open => PATH,
+                // Wall between a cell and the one below
+                (true, false) => expect_all(part, '-', (line, at))?,
+                // Wall between a cell and the one on the right
+                (false, true) => expect_all(part, '|', (line, at))?,
+                // Cell
+                (false, false) => {
+                    let i = part.iter().position(|c| !OPEN.contains(c)).unwrap_or(0);
+
+                    return Err(parse_error(
+                        (line, at + i),
+                        format!("unknown character {:?} in a cell", part[i]),
+                    ));
+                }
+            };
+        }
+    }
+
+    with_markers(Maze::new(width, height, cells)?, start, end)
+}
+
+// None if the maze isn't made of cells: its sides must be odd, the squares
+//  on even lines and columns walls and the ones on odd lines and columns open
+pub fn maze_to_classic(maze: &Maze, solution: &[Pos], cell_width: usize) -> Option<String> {
+    let (width, height) = (maze.width(), maze.height());
+    let cell_width = cell_width.max(1);
+
+    if width % 2 == 0 || height % 2 == 0 {
+        return None;
+    }
+
+    let mut on_solution = vec![false; maze.len()];
+
+    for &square in solution.iter().filter(|&&square| maze.contains(square)) {
+        on_solution[maze.index(square)] = true;
+    }
+
+    let start = maze.start().ok();
+    let end = maze.end().ok();
+
+    let mut lines = Vec::with_capacity(height);
+
+    for line in 0..height {
+        let mut chars = Vec::new();
+
+        for col in 0..width {
+            let square = (line, col);
+            let open = maze.is_open(square);
+
+            // What to write in the middle of an open square
+            let mark = if Some(square) == start {
+                'S'
+            } else if Some(square) == end {
+                'E'
+            } else if on_solution[maze.index(square)] {
+                '*'
+            } else {
+                ' '
+            };
+
+            match (line % 2 == 0, col % 2 == 0) {
+                (true, true) if open => return None,
+                (true, true) => chars.push('+'),
+                (true, false) if open => chars.extend(centered(mark, cell_width)),
+                (true, false) => chars.extend(vec!['-'; cell_width]),
+                (false, true) if open => chars.push(mark),
+                (false, true) => chars.push('|'),
+                (false, false) if open => chars.extend(centered(mark, cell_width)),
+                (false, false) => return None,
+            }
+        }
+
+        lines.push(chars);
+    }
+
+    Some(lines_to_string(lines.iter().map(|chars| &chars[..])))
+}
+
+// A classic maze if the first line starts with a corner, otherwise
+//  one character per square
+pub fn load_text(path: &str, charset: &Charset) -> Result<Maze, MazeError> {
+    let text = fs::read_to_string(path).map_err(MazeError::Io)?;
+
+    if text.starts_with('+') {
+        classic_to_maze(&text)
+    } else {
+        text_to_maze(&text, charset)
+    }
+}
+
+// ---------
+// Utilities
+// ---------
+// Every line as characters, the empty lines at the end don't count
+fn text_lines(text: &str) -> Result<Vec<Vec<char>>, MazeError> {
+    let mut lines: Vec<Vec<char>> = text
+        .lines()
+        .map(|line| line.trim_end_matches('\r').chars().collect())
+        .collect();
+
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    match lines.first() {
+        Some(line) if !line.is_empty() => Ok(lines),
+        _ => Err(MazeError::EmptyMaze),
+    }
+}
+
+fn lines_to_string<'a>(lines: impl Iterator<Item = &'a [char]>) -> String {
+    let mut text = String::new();
+
+    for line in lines {
+        text.extend(line);
+        text.push('\n');
+    }
+
+    text
+}
+
+// `at` is where the marker is in the text
+fn set_marker(marker: &mut Option<Pos>, square: Pos, at: Pos, name: &str) -> Result<(), MazeError> {
+    if marker.is_some() {
+        return Err(parse_error(at, format!("more than one {} marker", name)));
+    }
+
+    *marker = Some(square);
+
+    Ok(())
+}
+
+fn with_markers(maze: Maze, start: Option<Pos>, end: Option<Pos>) -> Result<Maze, MazeError> {
+    let maze = match start {
+        Some(start) => maze.with_start(start)?,
+        None => maze,
+    };
+
+    match end {
+        Some(end) => maze.with_end(end),
+        None => Ok(maze),
+    }
+}
+
+// `at` is where `part` starts in the text
+fn expect_all(part: &[char], expected: char, (line, col): Pos) -> Result<u8, MazeError> {
+    match part.iter().position(|&c| c != expected) {
+        Some(i) => Err(parse_error(
+            (line, col + i),
+            format!("expected {:?}, found {:?}", expected, part[i]),
+        )),
+        None => Ok(WALL),
+    }
+}
+
+// What can be where there's no wall in a classic maze
+const OPEN: [char; 5] = [' ', '.', '*', 'S', 'E'];
+
+// True if nothing in `part` is a wall, the markers in it are set
+fn open_part(
+    part: &[char],
+    square: Pos,
+    (line, col): Pos,
+    start: &mut Option<Pos>,
+    end: &mut Option<Pos>,
+) -> Result<bool, MazeError> {
+    if !part.iter().all(|c| OPEN.contains(c)) {
+        return Ok(false);
+    }
+
+    for (i, &c) in part.iter().enumerate() {
+        match c {
+            'S' => set_marker(start, square, (line, col + i), "start")?,
+            'E' => set_marker(end, square, (line, col + i), "end")?,
+            _ => {}
+        }
+    }
+
+    Ok(true)
+}
+
+// `mark` in the middle of `width` spaces
+fn centered(mark: char, width: usize) -> Vec<char> {
+    let mut chars = vec![' '; width];
+    chars[(width - 1) / 2] = mark;
+    chars
+}
+
+// Lines and columns start at 1 in the messages
+fn parse_error((line, column): Pos, reason: String) -> MazeError {
+    MazeError::Parse {
+        line: line + 1,
+        column: column + 1,
+        reason,
+    }
+}
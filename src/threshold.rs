@@ -0,0 +1,244 @@
+use image::DynamicImage;
+
+use crate::constants::{PATH, WALL};
+use crate::{Maze, MazeError, WALL_LIMIT};
+
+use std::convert::TryFrom;
+
+// How a pixel is told to be a wall or a path
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThresholdMethod {
+    // A wall if its red, green and blue are all below their threshold
+    Channels { red: u8, green: u8, blue: u8 },
+    // A wall if its luma is below the threshold
+    Luminance(u8),
+    // The luminance threshold that best splits the image in two groups,
+    //  for scans where the walls aren't black
+    Otsu,
+    // Each pixel gets its own threshold from the pixels around it
+    //  (Sauvola's method), for uneven lighting and anti-aliasing.
+    //  `radius` is in pixels, `k` is usually around 0.2, higher makes fewer walls
+    Adaptive { radius: u32, k: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThresholdConfig {
+    pub method: ThresholdMethod,
+    // Pixels with an alpha below it are walls whatever their color,
+    //  None to ignore the alpha
+    pub transparent_walls: Option<u8>,
+}
+
+impl Default for ThresholdConfig {
+    // What img_to_vec does
+    fn default() -> Self {
+        Self {
+            method: ThresholdMethod::Channels {
+                red: WALL_LIMIT,
+                green: WALL_LIMIT,
+                blue: WALL_LIMIT,
+            },
+            transparent_walls: None,
+        }
+    }
+}
+
+// The threshold that was used, pixels below it are walls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppliedThreshold {
+    Channels { red: u8, green: u8, blue: u8 },
+    Luminance(u8),
+    // The lowest and the highest of the thresholds of the pixels,
+    //  the pixels at their threshold are walls too
+    Local { min: u8, max: u8 },
+}
+
+// ITU-R BT.601 luma, from 0 (black) to 255 (white)
+pub(crate) fn luma(r: u8, g: u8, b: u8) -> u8 {
+    ((299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b)) / 1000) as u8
+}
+
+pub fn img_to_vec_with_config(
+    img: &DynamicImage,
+    config: &ThresholdConfig,
+) -> Result<(Vec<Vec<u8>>, AppliedThreshold), MazeError> {
+    // Will panic on a 16-bit platform
+    let (width, height) = (
+        usize::try_from(img.width()).unwrap(),
+        usize::try_from(img.height()).unwrap(),
+    );
+
+    if width == 0 || height == 0 {
+        return Err(MazeError::EmptyMaze);
+    }
+
+    let img = img.to_rgba8();
+
+    let transparent: Vec<bool> = img
+        .pixels()
+        .map(|pixel| {
+            config
+                .transparent_walls
+                .is_some_and(|limit| pixel[3] < limit)
+        })
+        .collect();
+
+    let lumas: Vec<u8> = img
+        .pixels()
+        .map(|pixel| luma(pixel[0], pixel[1], pixel[2]))
+        .collect();
+
+    let (walls, applied): (Vec<bool>, AppliedThreshold) = match config.method {
+        ThresholdMethod::Channels { red, green, blue } => (
+            img.pixels()
+                .map(|pixel| pixel[0] < red && pixel[1] < green && pixel[2] < blue)
+                .collect(),
+            AppliedThreshold::Channels { red, green, blue },
+        ),
+        ThresholdMethod::Luminance(threshold) => (
+            lumas.iter().map(|&luma| luma < threshold).collect(),
+            AppliedThreshold::Luminance(threshold),
+        ),
+        ThresholdMethod::Otsu => {
+            // The transparent pixels are walls already, they'd skew the split
+            let threshold = otsu(
+                lumas
+                    .iter()
+                    .zip(&transparent)
+                    .filter(|&(_, &transparent)| !transparent)
+                    .map(|(&luma, _)| luma),
+            );
+
+            (
+                lumas.iter().map(|&luma| luma < threshold).collect(),
+                AppliedThreshold::Luminance(threshold),
+            )
+        }
+        ThresholdMethod::Adaptive { radius, k } => {
+            sauvola(&lumas, width, height, radius as usize, k)
+        }
+    };
+
+    let cells: Vec<u8> = walls
+        .iter()
+        .zip(&transparent)
+        .map(|(&wall, &transparent)| if wall || transparent { WALL } else { PATH })
+        .collect();
+
+    Ok((
+        cells.chunks(width).map(|row| row.to_vec()).collect(),
+        applied,
+    ))
+}
+
+pub fn img_to_maze_with_config(
+    img: &DynamicImage,
+    config: &ThresholdConfig,
+) -> Result<(Maze, AppliedThreshold), MazeError> {
+    let (rows, applied) = img_to_vec_with_config(img, config)?;
+
+    Ok((Maze::from_rows(&rows)?, applied))
+}
+
+// The threshold that maximizes the variance between the pixels below it
+//  and the ones above it. 128 if all the pixels are alike
+fn otsu(lumas: impl Iterator<Item = u8>) -> u8 {
+    let mut histogram = [0u64; 256];
+
+    for luma in lumas {
+        histogram[usize::from(luma)] += 1;
+    }
+
+    let total: u64 = histogram.iter().sum();
+    let sum: u64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(luma, &count)| luma as u64 * count)
+        .sum();
+
+    let (mut below, mut below_sum) = (0u64, 0u64);
+    let mut best = (0.0, 128);
+
+    for (threshold, &count) in histogram.iter().enumerate().take(255) {
+        below += count;
+        below_sum += threshold as u64 * count;
+
+        let above = total - below;
+
+        if below == 0 || above == 0 {
+            continue;
+        }
+
+        let mean_below = below_sum as f64 / below as f64;
+        let mean_above = (sum - below_sum) as f64 / above as f64;
+        let variance = below as f64 * above as f64 * (mean_below - mean_above).powi(2);
+
+        // The pixels at `threshold` are walls, so the ones below the next value are
+        if variance > best.0 {
+            best = (variance, threshold as u8 + 1);
+        }
+    }
+
+    best.1
+}
+
+// mean * (1 + k * (deviation / 128 - 1)) over the square of `radius`
+//  around each pixel, with sums over rectangles so it's linear in the pixels.
+//  A pixel is a wall if it's at or below its threshold, so flat black
+//  areas stay walls and flat white ones paths
+fn sauvola(
+    lumas: &[u8],
+    width: usize,
+    height: usize,
+    radius: usize,
+    k: f64,
+) -> (Vec<bool>, AppliedThreshold) {
+    // sums[(y * (width + 1)) + x] is the sum of the pixels above and left of (x, y)
+    let stride = width + 1;
+    let mut sums = vec![0u64; stride * (height + 1)];
+    let mut squares = vec![0u64; stride * (height + 1)];
+
+    for y in 0..height {
+        let (mut line, mut line_squares) = (0u64, 0u64);
+
+        for x in 0..width {
+            let luma = u64::from(lumas[y * width + x]);
+
+            line += luma;
+            line_squares += luma * luma;
+
+            sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + line;
+            squares[(y + 1) * stride + x + 1] = squares[y * stride + x + 1] + line_squares;
+        }
+    }
+
+    let area = |table: &[u64], (x0, y0): (usize, usize), (x1, y1): (usize, usize)| {
+        table[y1 * stride + x1] + table[y0 * stride + x0]
+            - table[y0 * stride + x1]
+            - table[y1 * stride + x0]
+    };
+
+    let (mut min, mut max) = (u8::MAX, u8::MIN);
+
+    let walls = (0..width * height)
+        .map(|i| {
+            let (x, y) = (i % width, i / width);
+
+            let from = (x.saturating_sub(radius), y.saturating_sub(radius));
+            let to = ((x + radius + 1).min(width), (y + radius + 1).min(height));
+            let count = ((to.0 - from.0) * (to.1 - from.1)) as f64;
+
+            let mean = area(&sums, from, to) as f64 / count;
+            let variance = (area(&squares, from, to) as f64 / count - mean * mean).max(0.0);
+            let threshold = mean * (1.0 + k * (variance.sqrt() / 128.0 - 1.0));
+
+            let clamped = threshold.clamp(0.0, 255.0) as u8;
+            min = min.min(clamped);
+            max = max.max(clamped);
+
+            f64::from(lumas[i]) <= threshold
+        })
+        .collect();
+
+    (walls, AppliedThreshold::Local { min, max })
+}
@@ -0,0 +1,221 @@
+use crate::algorithms::{breadth_first, dijkstra};
+use crate::{Graph, Maze, MazeError, Pos};
+
+use std::fmt;
+
+// What validate_path_with looks at besides the path being a legal walk
+//  from the start to the end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checks {
+    // No square is stepped on twice
+    pub simple: bool,
+    // No path costs less, found with a breadth first search
+    //  or with Dijkstra on a weighted maze
+    pub optimal: bool,
+}
+
+impl Default for Checks {
+    fn default() -> Self {
+        Self {
+            simple: true,
+            optimal: false,
+        }
+    }
+}
+
+// Why a path isn't a solution, `index` is where it is in the path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    Empty,
+    WrongStart {
+        expected: Pos,
+        found: Pos,
+    },
+    OutOfBounds {
+        index: usize,
+        square: Pos,
+    },
+    Wall {
+        index: usize,
+        square: Pos,
+    },
+    // The square isn't next to the one before it
+    NotAdjacent {
+        index: usize,
+        from: Pos,
+        to: Pos,
+    },
+    Repeated {
+        index: usize,
+        square: Pos,
+        first: usize,
+    },
+    WrongEnd {
+        expected: Pos,
+        found: Pos,
+    },
+    NotOptimal {
+        cost: u64,
+        best: u64,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::Empty => write!(f, "the path is empty"),
+            Violation::WrongStart { expected, found } => write!(
+                f,
+                "the path starts on {:?} instead of {:?}",
+                found, expected
+            ),
+            Violation::OutOfBounds { index, square } => {
+                write!(f, "step {} is outside of the maze, on {:?}", index, square)
+            }
+            Violation::Wall { index, square } => {
+                write!(f, "step {} goes through the wall on {:?}", index, square)
+            }
+            Violation::NotAdjacent { index, from, to } => write!(
+                f,
+                "step {} jumps from {:?} to {:?}, which aren't next to each other",
+                index, from, to
+            ),
+            Violation::Repeated {
+                index,
+                square,
+                first,
+            } => write!(
+                f,
+                "step {} goes back to {:?}, already reached at step {}",
+                index, square, first
+            ),
+            Violation::WrongEnd { expected, found } => {
+                write!(f, "the path ends on {:?} instead of {:?}", found, expected)
+            }
+            Violation::NotOptimal { cost, best } => {
+                write!(f, "the path costs {}, the best one costs {}", cost, best)
+            }
+        }
+    }
+}
+
+// What was found about a path, only the first violation is kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathReport {
+    // Squares in the path, the start and the end included
+    pub length: usize,
+    // See Maze::path_cost, None unless the path is a legal walk
+    pub cost: Option<u64>,
+    pub violation: Option<Violation>,
+}
+
+impl PathReport {
+    pub fn is_valid(&self) -> bool {
+        self.violation.is_none()
+    }
+}
+
+// Validates with the default checks, a simple path that doesn't
+//  have to be the shortest
+pub fn validate_path(maze: &Maze, path: &[Pos]) -> Result<PathReport, MazeError> {
+    validate_path_with(maze, path, Checks::default())
+}
+
+// The steps are checked in order, so the violation is the first one
+//  along the path. Fails if the maze has no start or no end
+pub fn validate_path_with(
+    maze: &Maze,
+    path: &[Pos],
+    checks: Checks,
+) -> Result<PathReport, MazeError> {
+    let start = maze.start()?;
+    let end = maze.end()?;
+
+    let report = |violation| PathReport {
+        length: path.len(),
+        cost: None,
+        violation: Some(violation),
+    };
+
+    let (&first, &last) = match (path.first(), path.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Ok(report(Violation::Empty)),
+    };
+
+    if first != start {
+        return Ok(report(Violation::WrongStart {
+            expected: start,
+            found: first,
+        }));
+    }
+
+    // Where each square was first reached
+    let mut reached = vec![None; maze.len()];
+
+    for (index, &square) in path.iter().enumerate() {
+        if !maze.contains(square) {
+            return Ok(report(Violation::OutOfBounds { index, square }));
+        }
+
+        if !maze.is_open(square) {
+            return Ok(report(Violation::Wall { index, square }));
+        }
+
+        if index > 0 && !adjacent(path[index - 1], square) {
+            return Ok(report(Violation::NotAdjacent {
+                index,
+                from: path[index - 1],
+                to: square,
+            }));
+        }
+
+        match reached[maze.index(square)] {
+            Some(first) if checks.simple => {
+                return Ok(report(Violation::Repeated {
+                    index,
+                    square,
+                    first,
+                }))
+            }
+            Some(_) => {}
+            None => reached[maze.index(square)] = Some(index),
+        }
+    }
+
+    if last != end {
+        return Ok(report(Violation::WrongEnd {
+            expected: end,
+            found: last,
+        }));
+    }
+
+    let cost = maze.path_cost(path);
+
+    let violation = if checks.optimal {
+        let best = if maze.is_weighted() {
+            dijkstra(maze)?
+        } else {
+            breadth_first(maze, &Graph::from_maze(maze))?
+        }
+        .cost
+        .unwrap_or(0);
+
+        if cost > best {
+            Some(Violation::NotOptimal { cost, best })
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    Ok(PathReport {
+        length: path.len(),
+        cost: Some(cost),
+        violation,
+    })
+}
+
+fn adjacent((l1, c1): Pos, (l2, c2): Pos) -> bool {
+    l1.abs_diff(l2) + c1.abs_diff(c2) == 1
+}